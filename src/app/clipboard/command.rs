@@ -0,0 +1,90 @@
+// ============================================================================
+// src/app/clipboard/command.rs
+// ============================================================================
+//
+// 外部コマンドで clipboard に書き込むバックエンド。
+//
+//   wl-copy / xclip -selection clipboard / pbcopy / tmux load-buffer -
+//
+// のように、標準入力から読んだテキストを clipboard に設定するコマンドを
+// シェル経由で実行し、テキストを標準入力に流し込む。
+// 複数のコマンド（CLIPBOARD と PRIMARY の両方に書き込むプリセットなど）は
+// 順に実行し、それぞれに同じテキストを渡す。
+// クリアは空のテキストを渡して行う。
+// ============================================================================
+
+use std::io::Write;
+use std::process::Stdio;
+
+use anyhow::{bail, Context, Result};
+
+use super::ClipboardSink;
+use crate::app::external::shell_command;
+
+// -----------------------------------------------------------------------------
+// CommandClipboard
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandClipboard {
+    command_lines: Vec<String>,
+}
+
+impl CommandClipboard {
+    pub fn new(command_line: impl Into<String>) -> Self {
+        Self { command_lines: vec![command_line.into()] }
+    }
+
+    /// 複数のコマンドを順に実行する CommandClipboard
+    pub fn with_commands(command_lines: Vec<String>) -> Self {
+        Self { command_lines }
+    }
+
+    /// すべてのコマンドに text を渡す（失敗したらそこで止める）
+    fn run_all(&self, text: &str) -> Result<()> {
+        self.command_lines
+            .iter()
+            .try_for_each(|command_line| run(command_line, text))
+    }
+}
+
+impl ClipboardSink for CommandClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.run_all(text)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.run_all("")
+    }
+}
+
+// -----------------------------------------------------------------------------
+// run
+// -----------------------------------------------------------------------------
+//
+// コマンドを実行し、text を標準入力に渡して終了を待つ。
+//
+// xclip や wl-copy は clipboard を保持するためにバックグラウンドに残るので、
+// 標準出力はつながず（null）、パイプが閉じられるのを待って固まらないようにする。
+// -----------------------------------------------------------------------------
+fn run(command_line: &str, text: &str) -> Result<()> {
+    let mut child = shell_command(command_line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .with_context(|| {
+            format!("コマンドを起動できません: {}", command_line)
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).with_context(|| {
+            format!("コマンドに書き込めません: {}", command_line)
+        })?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("コマンドが失敗しました（{}）: {}", status, command_line);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+// ============================================================================
+// src/app/clipboard/file.rs
+// ============================================================================
+//
+// clipboard の代わりにファイルへ書き出すバックエンド。
+//
+// 取り込むたびにファイルを上書きし、クリアでは空にする。
+// 読み出し（get_text）ではファイルの内容を返す（なければ None）。
+// clipboard のないヘッドレスのマシンや、テスト・スクリプトから結果を
+// 確かめたい場合に使う。
+// ============================================================================
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::ClipboardSink;
+
+// -----------------------------------------------------------------------------
+// FileClipboard
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileClipboard {
+    path: PathBuf,
+}
+
+impl FileClipboard {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn write(&self, text: &str) -> Result<()> {
+        std::fs::write(&self.path, text).with_context(|| {
+            format!("ファイルに書き込めません: {}", self.path.display())
+        })
+    }
+}
+
+impl ClipboardSink for FileClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.write(text)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.write("")
+    }
+
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) if !text.is_empty() => Ok(Some(text)),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| {
+                format!("ファイルを読み込めません: {}", self.path.display())
+            }),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// ============================================================================
+// src/app/clipboard/hold.rs
+// ============================================================================
+//
+// 終了後も clipboard の内容を残すための「保持プロセス」。
+//
+// X11 / Wayland では、clipboard の内容はコピーしたプロセスが持ち続け、
+// 貼り付けのたびにそのプロセスが渡す。プロセスが終わると内容も消えるため、
+// clip_frag が終了した後には最後のフラグメントを貼り付けられない
+// （exit_loop で待ち続けているのはこのため）。
+//
+// K(eep) で終了すると、clip_frag は自分自身を `clip_frag hold` として
+// バックグラウンドで起動し、フラグメントを標準入力で渡して終了する。
+// hold は次のどちらかまで内容を持ち続け、その後は何もせずに終了する：
+//
+//   - 他のアプリが clipboard に別の内容をコピーした（置き換えられた）
+//   - --hold-timeout の秒数（既定 600 秒）が過ぎた
+//
+// clipboard を OS が持つ Windows / macOS では、保持プロセスは要らない。
+// ============================================================================
+
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use arboard::Clipboard;
+
+use super::Selection;
+
+/// 保持プロセスが内容を持ち続ける既定の秒数
+pub const DEFAULT_HOLD_TIMEOUT: u64 = 600;
+
+// -----------------------------------------------------------------------------
+// spawn_hold
+// -----------------------------------------------------------------------------
+//
+// `clip_frag hold` をバックグラウンドで起動し、text を渡す。
+// 書き込み先は --selection で渡す。
+// 保持プロセスの終了は待たない。端末の Ctrl-C などで一緒に終了しないよう、
+// Unix 系 OS では別のプロセスグループにする。
+// -----------------------------------------------------------------------------
+pub fn spawn_hold(
+    text: &str,
+    timeout: Duration,
+    selection: Selection,
+) -> Result<()> {
+    let exe = std::env::current_exe()
+        .context("clip_frag の実行ファイルが見つかりません")?;
+
+    let mut command = Command::new(exe);
+    command
+        .arg("hold")
+        .arg("--timeout")
+        .arg(timeout.as_secs().to_string())
+        .arg("--selection")
+        .arg(selection.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command.spawn().context("保持プロセスを起動できません")?;
+
+    // 書き終えたら stdin を閉じ、hold に入力の終わりを知らせる
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin
+        .write_all(text.as_bytes())
+        .context("保持プロセスに内容を渡せません")?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// run_hold
+// -----------------------------------------------------------------------------
+//
+// `clip_frag hold` の本体。標準入力を読み、clipboard に設定して、
+// 置き換えられるか timeout が過ぎるまで持ち続ける。
+// 書き込み先（CLIPBOARD / PRIMARY）は selection（--selection）で選ぶ。
+// -----------------------------------------------------------------------------
+pub fn run_hold(timeout: Duration, selection: Selection) -> Result<()> {
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .context("標準入力を読み込めません")?;
+
+    let mut clipboard =
+        Clipboard::new().context("failed to open clipboard")?;
+
+    hold_text(&mut clipboard, selection, text, timeout)
+}
+
+// -----------------------------------------------------------------------------
+// hold_text
+// -----------------------------------------------------------------------------
+//
+// both の場合は PRIMARY を先に設定し、CLIPBOARD が置き換えられるまで待つ
+// （PRIMARY もそれまでは同じプロセスが持ち続ける）。
+// -----------------------------------------------------------------------------
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+))]
+fn hold_text(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+    text: String,
+    timeout: Duration,
+) -> Result<()> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
+    let deadline = std::time::Instant::now() + timeout;
+
+    let kind = if selection.includes_clipboard() {
+        if selection.includes_primary() {
+            clipboard
+                .set()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text(text.clone())?;
+        }
+        LinuxClipboardKind::Clipboard
+    } else {
+        LinuxClipboardKind::Primary
+    };
+
+    clipboard
+        .set()
+        .clipboard(kind)
+        .wait_until(deadline)
+        .text(text)
+        .context("failed to set clipboard text")?;
+
+    Ok(())
+}
+
+// clipboard を OS が持つ環境では、設定するだけでよい
+#[cfg(not(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+)))]
+fn hold_text(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+    text: String,
+    _timeout: Duration,
+) -> Result<()> {
+    super::selection::write_text(clipboard, selection, &text)
+        .context("failed to set clipboard text")
+}
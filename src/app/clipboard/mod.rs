@@ -0,0 +1,310 @@
+//! ============================================================================
+//! src/app/clipboard/mod.rs
+//! ============================================================================
+//!
+//! このモジュールは、Windows / macOS / Linux のすべてで動作する
+//! クロスプラットフォームなクリップボード操作を提供する。
+//!
+//! 以前は clipboard-win を使用していたが、Windows 専用であり
+//! CI（Ubuntu）や macOS では動作しなかった。
+//!
+//! 現在は arboard を採用し、以下のメリットを得ている：
+//!   - Windows / macOS / Linux すべてで動作
+//!   - UTF-8 ベースで Rust の String と相性が良い
+//!   - エラー型が std::error::Error を実装しており anyhow と相性抜群
+//!   - OS ごとの API を意識せずに統一的に扱える
+//!
+//! clip_frag の設計思想（責務分離・安全ラップ）に完全に一致する。
+//!
+//! arboard が使えない環境（ヘッドレスのマシンや、wl-copy などを
+//! 使いたい場合）のために、取り込み先（バックエンド）を切り替えられる：
+//!
+//!   - system.rs:  arboard（既定）
+//!   - command.rs: 外部コマンド（wl-copy / xclip / pbcopy / tmux など）
+//!   - file.rs:    ファイルへの書き出し（テストやヘッドレス環境向け）
+//!   - osc52.rs:   OSC 52 エスケープシーケンス（SSH 先から手元の端末へ）
+//!   - remote.rs:  別のマシンの `clip_frag serve` へ送る
+//!
+//! どれも ClipboardSink を実装し、--clipboard または設定ファイルの
+//! [clipboard] backend で選ぶ（ClipboardBackend）。
+//!
+//! 書き込み先（CLIPBOARD / PRIMARY）は selection.rs で選び、ClipboardBackend::build
+//! で SystemClipboard と wl-copy / xclip / xsel のプリセットに渡す。
+//! 終了後も内容を残す保持プロセス（`clip_frag hold`）は hold.rs。
+//! 書き込み後の読み戻しによる確認と再試行は verify.rs。
+//! --clear-after のための書き込み時刻の記録は timed.rs。
+//!
+//! --html では、フラグメントを HTML と代替テキストの組で取り込む
+//! （ClipboardSink::set_html）。HTML を扱えない取り込み先には代替テキストを
+//! 書き込む。
+//!
+//! ============================================================================
+
+mod command;
+mod file;
+mod hold;
+mod osc52;
+pub mod remote;
+mod selection;
+mod system;
+mod timed;
+mod verify;
+
+pub use command::CommandClipboard;
+pub use file::FileClipboard;
+pub use hold::{run_hold, spawn_hold, DEFAULT_HOLD_TIMEOUT};
+pub use osc52::{base64_encode, osc52_sequence, Multiplexer, Osc52Clipboard};
+pub use remote::{Endpoint, RemoteClipboard};
+pub use selection::Selection;
+pub use system::SystemClipboard;
+pub use timed::TimedClipboard;
+pub use verify::{
+    content_hash, set_html_verified, set_text_verified, ContentMismatch,
+    VERIFY_ATTEMPTS,
+};
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use arboard::Clipboard;
+use serde::Deserialize;
+
+// -----------------------------------------------------------------------------
+// set_clip_utf16（UTF-8 ベースのクロスプラットフォーム版）
+// -----------------------------------------------------------------------------
+//
+// Windows では UTF-16 が内部的に使われるが、arboard は UTF-8 を受け付けるため
+// UTF-16 を意識する必要はない。
+//
+// - クリップボードに text を設定する
+// - OS に依存しない
+// - anyhow::Result で安全に扱える
+// - CLIPBOARD に書き込む（PRIMARY へは SystemClipboard::new で選んで書き込む）
+// - 書き込んだ後に読み戻して確かめ、一致しなければ再試行する（verify.rs）
+// -----------------------------------------------------------------------------
+pub fn set_clip_utf16(text: impl AsRef<str>) -> Result<()> {
+    set_text_verified(&mut SystemClipboard::default(), text.as_ref())
+}
+
+// -----------------------------------------------------------------------------
+// clear_clipboard
+// -----------------------------------------------------------------------------
+//
+// クリップボードを空文字列で上書きすることでクリアする。
+// Windows / macOS / Linux すべてで動作する。
+// set_clip_utf16 と同じく、CLIPBOARD をクリアする。
+// -----------------------------------------------------------------------------
+pub fn clear_clipboard() -> Result<()> {
+    let mut clipboard =
+        Clipboard::new().context("failed to open clipboard")?;
+
+    selection::write_text(&mut clipboard, Selection::Clipboard, "")
+        .context("failed to clear clipboard")?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// ClipboardSink
+// -----------------------------------------------------------------------------
+//
+// App が取り込み先として使う clipboard の抽象。
+// 実際の clipboard（SystemClipboard）の代わりに、テストではメモリ上の
+// 実装を差し込める（App::with_clipboard）。
+// -----------------------------------------------------------------------------
+pub trait ClipboardSink {
+    /// clipboard に text を設定する
+    fn set_text(&mut self, text: &str) -> Result<()>;
+
+    /// clipboard をクリアする
+    fn clear(&mut self) -> Result<()>;
+
+    /// clipboard に HTML（リッチテキスト）と代替テキストを設定する
+    ///
+    /// 既定では代替テキストだけを設定する（HTML を扱えない取り込み先）。
+    fn set_html(&mut self, _html: &str, alt_text: &str) -> Result<()> {
+        self.set_text(alt_text)
+    }
+
+    /// get_text で書き込んだ内容を読み戻せるか（既定は false）
+    ///
+    /// true なら、書き込みのたびに読み戻して確かめる（set_text_verified）。
+    fn can_read(&self) -> bool {
+        false
+    }
+
+    /// clipboard の現在の内容を読む
+    ///
+    /// 読めない取り込み先（外部コマンド・OSC 52・serve）や、clipboard が
+    /// 空・テキスト以外の場合は None を返す。既定は常に None。
+    fn get_text(&mut self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// 終了後も text が clipboard に残るようにする（K(eep) で終了するとき）
+    ///
+    /// 既定では何もしない。OS が clipboard を持つ環境や、外部コマンド・
+    /// ファイル・serve のように書き込み先が内容を持ち続けるものはこれでよい。
+    fn persist(&mut self, _text: &str, _timeout: Duration) -> Result<()> {
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ClipboardBackend
+// -----------------------------------------------------------------------------
+//
+// --clipboard / 設定ファイルで指定する取り込み先。文字列から作る：
+//
+//   system（arboard）        OS の clipboard（既定）
+//   osc52                    OSC 52 で端末エミュレータに送る（SSH 越し）
+//   wl-copy / xclip / xsel / pbcopy / tmux
+//                            よく使うコマンドのプリセット
+//                            （wl-copy / xclip / xsel は書き込み先に従う）
+//   command:<コマンドライン> 任意のコマンド（標準入力にテキストを渡す）
+//   file:<パス>              ファイルに書き出す
+//   remote:<接続先>          clip_frag serve に送る
+//                            （127.0.0.1:7878 / unix:/path/to/socket）
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ClipboardBackend {
+    #[default]
+    System,
+    Osc52,
+    Preset(String),
+    Command(String),
+    File(PathBuf),
+    Remote(Endpoint),
+}
+
+impl ClipboardBackend {
+    /// 指定に従って、書き込み先が selection の ClipboardSink を作る
+    pub fn build(&self, selection: Selection) -> Box<dyn ClipboardSink> {
+        match self {
+            Self::System => Box::new(SystemClipboard::new(selection)),
+            Self::Osc52 => Box::new(Osc52Clipboard::default()),
+            Self::Preset(name) => Box::new(CommandClipboard::with_commands(
+                preset_commands(name, selection),
+            )),
+            Self::Command(command_line) => {
+                Box::new(CommandClipboard::new(command_line.clone()))
+            }
+            Self::File(path) => Box::new(FileClipboard::new(path.clone())),
+            Self::Remote(endpoint) => {
+                Box::new(RemoteClipboard::from_env(endpoint.clone()))
+            }
+        }
+    }
+}
+
+impl ClipboardBackend {
+    /// 書き込み先（--selection）で書き込む場所を変えられるか
+    pub fn honors_selection(&self) -> bool {
+        match self {
+            Self::System => true,
+            Self::Preset(name) => {
+                preset(name).is_some_and(|(_, primary)| primary.is_some())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for ClipboardBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+
+        if let Some(command_line) = spec.strip_prefix("command:") {
+            if command_line.trim().is_empty() {
+                bail!("command: の後にコマンドを指定してください");
+            }
+            return Ok(Self::Command(command_line.trim().to_string()));
+        }
+
+        if let Some(path) = spec.strip_prefix("file:") {
+            if path.is_empty() {
+                bail!("file: の後にパスを指定してください");
+            }
+            return Ok(Self::File(PathBuf::from(path)));
+        }
+
+        if let Some(endpoint) = spec.strip_prefix("remote:") {
+            return Ok(Self::Remote(endpoint.parse()?));
+        }
+
+        match spec {
+            "system" | "arboard" => Ok(Self::System),
+            "osc52" => Ok(Self::Osc52),
+            _ => match preset(spec) {
+                Some(_) => Ok(Self::Preset(spec.to_string())),
+                None => bail!(
+                    "不明な clipboard の指定です: {}（system / osc52 / wl-copy / xclip / xsel / pbcopy / tmux / command:CMD / file:PATH / remote:ADDR）",
+                    spec
+                ),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for ClipboardBackend {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: String) -> Result<Self> {
+        spec.parse()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// preset
+// -----------------------------------------------------------------------------
+//
+// よく使う clipboard コマンドのプリセット。
+// どれも標準入力から読んだテキストを clipboard（tmux はペーストバッファ）に
+// 設定する。CLIPBOARD に書き込むコマンドと、PRIMARY に書き込むコマンド
+// （PRIMARY のないものは None）の組を返す。
+// -----------------------------------------------------------------------------
+fn preset(name: &str) -> Option<(&'static str, Option<&'static str>)> {
+    match name {
+        "wl-copy" => Some(("wl-copy", Some("wl-copy --primary"))),
+        "xclip" => Some((
+            "xclip -selection clipboard",
+            Some("xclip -selection primary"),
+        )),
+        "xsel" => {
+            Some(("xsel --clipboard --input", Some("xsel --primary --input")))
+        }
+        "pbcopy" => Some(("pbcopy", None)),
+        "tmux" => Some(("tmux load-buffer -", None)),
+        _ => None,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// preset_commands
+// -----------------------------------------------------------------------------
+//
+// プリセット name で selection に書き込むためのコマンド（both なら 2 つ）。
+// PRIMARY のないプリセットは、selection に関係なく 1 つ。
+// -----------------------------------------------------------------------------
+pub fn preset_commands(name: &str, selection: Selection) -> Vec<String> {
+    let Some((clipboard, primary)) = preset(name) else {
+        return Vec::new();
+    };
+
+    match primary {
+        Some(primary) => [
+            (selection.includes_clipboard(), clipboard),
+            (selection.includes_primary(), primary),
+        ]
+        .into_iter()
+        .filter(|&(used, _)| used)
+        .map(|(_, command_line)| command_line.to_string())
+        .collect(),
+        None => vec![clipboard.to_string()],
+    }
+}
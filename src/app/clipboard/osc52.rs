@@ -0,0 +1,195 @@
+// ============================================================================
+// src/app/clipboard/osc52.rs
+// ============================================================================
+//
+// OSC 52 エスケープシーケンスで clipboard に書き込むバックエンド。
+//
+// SSH 先のマシンにはディスプレイがなく arboard が使えないが、
+// 手元の端末エミュレータ（xterm・iTerm2・WezTerm・kitty・Windows Terminal
+// など）は OSC 52 を受け取ると、手元の clipboard に設定してくれる。
+//
+//   ESC ] 52 ; c ; <base64 テキスト> BEL
+//
+// シーケンスは標準出力ではなく制御端末（/dev/tty）に書き込む。
+//
+// tmux / GNU screen の中では、そのままだと端末まで届かないため
+// パススルー用の DCS で包む（tmux では set -g allow-passthrough on、
+// または set -g set-clipboard on が必要）。
+//
+// 端末によっては受け付けるシーケンスの長さに上限があるため、
+// 大きなフラグメントでは警告を出す。
+// ============================================================================
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use anyhow::{Context, Result};
+
+use super::ClipboardSink;
+use crate::app::fragment::format_with_underscore;
+
+/// これを超える base64 のシーケンスは、端末によっては切り捨てられる
+pub const OSC52_WARN_BYTES: usize = 100_000;
+
+/// GNU screen の DCS 1 つに収めるバイト数（screen の上限は 768 バイト）
+const SCREEN_CHUNK_BYTES: usize = 76;
+
+/// OSC 52 を書き込む端末
+#[cfg(unix)]
+const TTY_OUTPUT: &str = "/dev/tty";
+
+#[cfg(windows)]
+const TTY_OUTPUT: &str = "CONOUT$";
+
+// -----------------------------------------------------------------------------
+// Multiplexer
+// -----------------------------------------------------------------------------
+//
+// 端末との間にある端末多重化ソフト。シーケンスの包み方が変わる。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    // -------------------------------------------------------------------------
+    // Multiplexer::detect
+    // -------------------------------------------------------------------------
+    //
+    // 環境変数から判定する。$TMUX があれば tmux、$STY があれば screen。
+    // -------------------------------------------------------------------------
+    pub fn detect() -> Self {
+        let has =
+            |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+
+        if has("TMUX") {
+            Self::Tmux
+        } else if has("STY") {
+            Self::Screen
+        } else {
+            Self::None
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Osc52Clipboard
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Osc52Clipboard {
+    multiplexer: Multiplexer,
+}
+
+impl Default for Osc52Clipboard {
+    fn default() -> Self {
+        Self::new(Multiplexer::detect())
+    }
+}
+
+impl Osc52Clipboard {
+    pub fn new(multiplexer: Multiplexer) -> Self {
+        Self { multiplexer }
+    }
+
+    /// 制御端末にシーケンスを書き込む
+    fn write(&self, payload: &str) -> Result<()> {
+        let sequence = osc52_sequence(payload, self.multiplexer);
+
+        let mut tty = OpenOptions::new()
+            .write(true)
+            .open(TTY_OUTPUT)
+            .with_context(|| format!("端末（{}）を開けません", TTY_OUTPUT))?;
+        tty.write_all(sequence.as_bytes())?;
+        tty.flush()?;
+
+        Ok(())
+    }
+}
+
+impl ClipboardSink for Osc52Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        let encoded = base64_encode(text.as_bytes());
+
+        if encoded.len() > OSC52_WARN_BYTES {
+            eprintln!(
+                "注意: OSC 52 のデータ（{} bytes）が大きいため、端末によっては取り込まれないことがあります。",
+                format_with_underscore(encoded.len())
+            );
+        }
+
+        self.write(&encoded)
+    }
+
+    // base64 でない文字列（"!"）を送ると、xterm などは選択をクリアする
+    fn clear(&mut self) -> Result<()> {
+        self.write("!")
+    }
+}
+
+// -----------------------------------------------------------------------------
+// osc52_sequence
+// -----------------------------------------------------------------------------
+//
+// payload（base64 済みのテキスト、またはクリア用の "!"）を OSC 52 の
+// シーケンスにし、multiplexer に応じて包む。
+//
+//   - tmux:   ESC P tmux; <ESC を二重にしたシーケンス> ESC \
+//   - screen: シーケンスを短く区切り、それぞれを ESC P ... ESC \ で包む
+// -----------------------------------------------------------------------------
+pub fn osc52_sequence(payload: &str, multiplexer: Multiplexer) -> String {
+    let osc = format!("\x1b]52;c;{}\x07", payload);
+
+    match multiplexer {
+        Multiplexer::None => osc,
+        Multiplexer::Tmux => {
+            format!("\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b"))
+        }
+        Multiplexer::Screen => osc
+            .as_bytes()
+            .chunks(SCREEN_CHUNK_BYTES)
+            .map(|chunk| {
+                // シーケンスは ASCII のみなので、どこで区切っても UTF-8 として正しい
+                format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk))
+            })
+            .collect(),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// base64_encode
+// -----------------------------------------------------------------------------
+//
+// 標準の base64（RFC 4648、パディングあり）。
+// 3 バイトずつ 24 ビットにまとめ、6 ビットずつ 4 文字に変換する。
+// -----------------------------------------------------------------------------
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n =
+            (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            // 入力が足りない部分は '=' で埋める
+            if i <= chunk.len() {
+                let index = (n >> (18 - 6 * i)) & 0x3f;
+                out.push(TABLE[index as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
@@ -0,0 +1,282 @@
+// ============================================================================
+// src/app/clipboard/remote.rs
+// ============================================================================
+//
+// 別のマシンで動いている `clip_frag serve` にフラグメントを送るバックエンドと、
+// その通信手順（プロトコル）。
+//
+// 手元のノート PC で serve を起動し、SSH のポート転送でリモートから
+// つなぐ使い方を想定している：
+//
+//   laptop$ CLIP_FRAG_TOKEN=secret clip_frag serve
+//   laptop$ ssh -R 7878:127.0.0.1:7878 build-host
+//   remote$ CLIP_FRAG_TOKEN=secret clip_frag --clipboard remote:127.0.0.1:7878 big.log
+//
+// プロトコル（1 行はすべて '\n' 終わり）：
+//
+//   client → server  CLIP_FRAG/1 <token>        接続直後のあいさつ
+//   server → client  OK / ERR <理由>            トークンが違えば切断する
+//   client → server  SET <バイト数> + 本文      clipboard に設定
+//   client → server  CLEAR                      clipboard をクリア
+//   server → client  OK / ERR <理由>            各コマンドへの応答
+//                                               （不正なコマンドなら ERR の後に切断する）
+//
+// ホスト上の他のユーザがポートにつないで clipboard を書き換えられないよう、
+// 共有トークン（環境変数 CLIP_FRAG_TOKEN）を知っている相手だけを受け付ける。
+// ============================================================================
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::ClipboardSink;
+
+/// 共有トークンを渡す環境変数
+pub const TOKEN_ENV: &str = "CLIP_FRAG_TOKEN";
+
+/// serve の既定の待ち受けアドレス（ローカルのみ）
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+/// あいさつ行の先頭
+pub const PROTOCOL: &str = "CLIP_FRAG/1";
+
+/// 1 回に受け付ける本文の上限
+pub const MAX_PAYLOAD: usize = 64 * 1024 * 1024;
+
+/// コマンド行・あいさつ行の長さの上限
+pub const MAX_LINE: u64 = 1024;
+
+// -----------------------------------------------------------------------------
+// Stream
+// -----------------------------------------------------------------------------
+//
+// TCP / Unix ドメインソケットをまとめて扱うための型。
+// -----------------------------------------------------------------------------
+pub trait Stream: Read + Write + Send {
+    /// 読み込みを待つ時間の上限を設定する（None なら無制限）
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl Stream for TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl Stream for std::os::unix::net::UnixStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        std::os::unix::net::UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Endpoint
+// -----------------------------------------------------------------------------
+//
+// 接続先・待ち受け先。"127.0.0.1:7878" のような TCP のアドレスか、
+// "unix:/path/to/socket"（Unix 系 OS のみ）。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    /// 接続する
+    pub fn connect(&self) -> Result<Box<dyn Stream>> {
+        match self {
+            Self::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)
+                    .with_context(|| format!("{} に接続できません", self))?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Self::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)
+                    .with_context(|| format!("{} に接続できません", self))?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            Self::Unix(_) => {
+                bail!("Unix ドメインソケットはこの OS では使えません")
+            }
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        match spec.strip_prefix("unix:") {
+            Some("") => bail!("unix: の後にソケットのパスを指定してください"),
+            Some(path) => Ok(Self::Unix(PathBuf::from(path))),
+            None if spec.is_empty() => bail!("接続先を指定してください"),
+            None => Ok(Self::Tcp(spec.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// RemoteClipboard
+// -----------------------------------------------------------------------------
+//
+// serve にフラグメントを送る ClipboardSink。
+// 最初の書き込みで接続し、以降は同じ接続を使い回す。
+// 接続が切れていた場合は、一度だけつなぎ直して送り直す。
+// -----------------------------------------------------------------------------
+pub struct RemoteClipboard {
+    endpoint: Endpoint,
+    token: Option<String>,
+    connection: Option<BufReader<Box<dyn Stream>>>,
+}
+
+impl RemoteClipboard {
+    pub fn new(endpoint: Endpoint, token: Option<String>) -> Self {
+        Self { endpoint, token, connection: None }
+    }
+
+    /// トークンを環境変数 CLIP_FRAG_TOKEN から読んで作る
+    pub fn from_env(endpoint: Endpoint) -> Self {
+        let token = std::env::var(TOKEN_ENV).ok().filter(|t| !t.is_empty());
+        Self::new(endpoint, token)
+    }
+
+    // -------------------------------------------------------------------------
+    // RemoteClipboard::connect
+    // -------------------------------------------------------------------------
+    //
+    // 接続してあいさつ（トークンの確認）を済ませる。
+    // -------------------------------------------------------------------------
+    fn connect(&self) -> Result<BufReader<Box<dyn Stream>>> {
+        let Some(ref token) = self.token else {
+            bail!(
+                "環境変数 {} にトークンを設定してください（serve 側と同じ値）",
+                TOKEN_ENV
+            );
+        };
+
+        let mut connection = BufReader::new(self.endpoint.connect()?);
+        send(&mut connection, &format!("{} {}", PROTOCOL, token), &[])
+            .with_context(|| format!("{} に接続できません", self.endpoint))?;
+
+        Ok(connection)
+    }
+
+    // -------------------------------------------------------------------------
+    // RemoteClipboard::request
+    // -------------------------------------------------------------------------
+    //
+    // コマンドを送り、応答を待つ。送れなかった場合は一度だけつなぎ直す。
+    // serve が ERR を返した場合はつなぎ直さない。
+    // -------------------------------------------------------------------------
+    fn request(&mut self, command: &str, body: &[u8]) -> Result<()> {
+        for attempt in 0..2 {
+            if self.connection.is_none() {
+                self.connection = Some(self.connect()?);
+            }
+            let connection = self.connection.as_mut().expect("connected");
+
+            match send(connection, command, body) {
+                Ok(()) => return Ok(()),
+                Err(e) if e.is::<Rejected>() => return Err(e),
+                Err(e) if attempt == 0 => {
+                    eprintln!("接続が切れたため、つなぎ直します: {:#}", e);
+                    self.connection = None;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        unreachable!("request は 2 回目までに必ず戻る")
+    }
+}
+
+impl ClipboardSink for RemoteClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.request(&format!("SET {}", text.len()), text.as_bytes())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.request("CLEAR", &[])
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Rejected
+// -----------------------------------------------------------------------------
+//
+// serve が ERR で応答したことを表すエラー（つなぎ直しても無駄なもの）。
+// -----------------------------------------------------------------------------
+#[derive(Debug)]
+struct Rejected(String);
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "serve に拒否されました: {}", self.0)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+// -----------------------------------------------------------------------------
+// send
+// -----------------------------------------------------------------------------
+//
+// 1 行のコマンド（と本文）を送り、OK / ERR の応答を読む。
+// -----------------------------------------------------------------------------
+fn send(
+    connection: &mut BufReader<Box<dyn Stream>>,
+    command: &str,
+    body: &[u8],
+) -> Result<()> {
+    let stream = connection.get_mut();
+    stream.write_all(command.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let response = read_line(connection)?;
+    match response.strip_prefix("ERR") {
+        Some(reason) => Err(Rejected(reason.trim().to_string()).into()),
+        None if response == "OK" => Ok(()),
+        None => Err(anyhow!("serve からの応答が不正です: {}", response)),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// read_line
+// -----------------------------------------------------------------------------
+//
+// 1 行（最大 MAX_LINE バイト）を読み、末尾の改行を除いて返す。
+// 相手が接続を閉じていた場合はエラーにする。
+// -----------------------------------------------------------------------------
+pub fn read_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE).read_line(&mut line)?;
+
+    if line.is_empty() {
+        bail!("接続が閉じられました");
+    }
+    if !line.ends_with('\n') {
+        bail!("行が長すぎます");
+    }
+
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,271 @@
+// ============================================================================
+// src/app/clipboard/selection.rs
+// ============================================================================
+//
+// X11 / Wayland の書き込み先（CLIPBOARD / PRIMARY）の選択。
+//
+// Linux では Ctrl-V で貼り付ける CLIPBOARD とは別に、中クリックで
+// 貼り付ける PRIMARY セレクションがある。--selection（または設定ファイルの
+// [clipboard] selection）で、arboard を使う書き込み（SystemClipboard・
+// 保持プロセス）と、wl-copy / xclip / xsel のプリセットの書き込み先を選べる。
+//
+//   clipboard  CLIPBOARD のみ（既定）
+//   primary    PRIMARY のみ
+//   both       両方
+//
+// 選んだ書き込み先は SystemClipboard が持ち、保持プロセスには
+// `clip_frag hold --selection` で渡す。
+// PRIMARY のない OS（Windows / macOS）では CLIPBOARD に書き込む。
+// ============================================================================
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use arboard::Clipboard;
+use serde::Deserialize;
+
+// -----------------------------------------------------------------------------
+// Selection
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Selection {
+    #[default]
+    Clipboard,
+    Primary,
+    Both,
+}
+
+impl Selection {
+    /// CLIPBOARD に書き込むか
+    pub fn includes_clipboard(self) -> bool {
+        matches!(self, Self::Clipboard | Self::Both)
+    }
+
+    /// PRIMARY に書き込むか
+    pub fn includes_primary(self) -> bool {
+        matches!(self, Self::Primary | Self::Both)
+    }
+
+    /// --selection に渡す名前（FromStr の逆）
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Clipboard => "clipboard",
+            Self::Primary => "primary",
+            Self::Both => "both",
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        match spec.trim() {
+            "clipboard" => Ok(Self::Clipboard),
+            "primary" => Ok(Self::Primary),
+            "both" => Ok(Self::Both),
+            other => bail!(
+                "不明な selection の指定です: {}（clipboard / primary / both）",
+                other
+            ),
+        }
+    }
+}
+
+impl TryFrom<String> for Selection {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: String) -> Result<Self> {
+        spec.parse()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// write_text
+// -----------------------------------------------------------------------------
+//
+// selection の書き込み先すべてに text を書き込む。
+// -----------------------------------------------------------------------------
+pub(super) fn write_text(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+    text: &str,
+) -> Result<()> {
+    if selection.includes_clipboard() {
+        clipboard.set_text(text.to_string())?;
+    }
+    if selection.includes_primary() {
+        write_primary(clipboard, selection, text)?;
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// write_html
+// -----------------------------------------------------------------------------
+//
+// write_text の HTML 版。HTML と代替テキストを書き込む。
+// -----------------------------------------------------------------------------
+pub(super) fn write_html(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+    html: &str,
+    alt_text: &str,
+) -> Result<()> {
+    if selection.includes_clipboard() {
+        clipboard.set_html(html, Some(alt_text))?;
+    }
+    if selection.includes_primary() {
+        write_primary_html(clipboard, selection, html, alt_text)?;
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// read_text
+// -----------------------------------------------------------------------------
+//
+// selection の書き込み先の内容を読む。both の場合は CLIPBOARD を読む。
+// 空・テキスト以外で読めない場合は None。
+// -----------------------------------------------------------------------------
+pub(super) fn read_text(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+) -> Option<String> {
+    if selection.includes_clipboard() {
+        clipboard.get_text().ok()
+    } else {
+        read_primary(clipboard)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// write_primary
+// -----------------------------------------------------------------------------
+//
+// PRIMARY セレクションに書き込む（X11 / Wayland）。
+// -----------------------------------------------------------------------------
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+))]
+fn write_primary(
+    clipboard: &mut Clipboard,
+    _selection: Selection,
+    text: &str,
+) -> Result<()> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
+    clipboard
+        .set()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text(text.to_string())?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// read_primary
+// -----------------------------------------------------------------------------
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+))]
+fn read_primary(clipboard: &mut Clipboard) -> Option<String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    clipboard.get().clipboard(LinuxClipboardKind::Primary).text().ok()
+}
+
+// PRIMARY のない OS では CLIPBOARD を読む
+#[cfg(not(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+)))]
+fn read_primary(clipboard: &mut Clipboard) -> Option<String> {
+    clipboard.get_text().ok()
+}
+
+// -----------------------------------------------------------------------------
+// write_primary_html
+// -----------------------------------------------------------------------------
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+))]
+fn write_primary_html(
+    clipboard: &mut Clipboard,
+    _selection: Selection,
+    html: &str,
+    alt_text: &str,
+) -> Result<()> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
+    clipboard
+        .set()
+        .clipboard(LinuxClipboardKind::Primary)
+        .html(html, Some(alt_text))?;
+
+    Ok(())
+}
+
+#[cfg(not(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+)))]
+fn write_primary_html(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+    html: &str,
+    alt_text: &str,
+) -> Result<()> {
+    if selection == Selection::Primary {
+        clipboard.set_html(html, Some(alt_text))?;
+    }
+
+    Ok(())
+}
+
+// PRIMARY のない OS では CLIPBOARD に書き込む（both なら書き込み済み）
+#[cfg(not(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+)))]
+fn write_primary(
+    clipboard: &mut Clipboard,
+    selection: Selection,
+    text: &str,
+) -> Result<()> {
+    if selection == Selection::Primary {
+        clipboard.set_text(text.to_string())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// ============================================================================
+// src/app/clipboard/system.rs
+// ============================================================================
+//
+// OS の clipboard（arboard）に書き込むバックエンド。
+//
+// set_clip_utf16 / clear_clipboard は呼ぶたびに arboard::Clipboard を
+// 作り直すが、こちらは最初に使うときに一度だけ開き、セッション中は
+// 使い回す。開くのを最初の書き込みまで遅らせるので、別のバックエンドに
+// 差し替えた場合やテストでは、ディスプレイがなくても App を作れる。
+//
+// 書き込み先（CLIPBOARD / PRIMARY）は作るときに選ぶ（SystemClipboard::new）。
+//
+// X11 / Wayland では、終了後も内容を残すために保持プロセスを起動する
+// （persist、hold.rs）。
+// ============================================================================
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use arboard::Clipboard;
+
+use super::selection::{read_text, write_html, write_text};
+use super::{ClipboardSink, Selection};
+
+// -----------------------------------------------------------------------------
+// SystemClipboard
+// -----------------------------------------------------------------------------
+#[derive(Default)]
+pub struct SystemClipboard {
+    clipboard: Option<Clipboard>,
+
+    /// 書き込み先（CLIPBOARD / PRIMARY）
+    selection: Selection,
+}
+
+impl SystemClipboard {
+    pub fn new(selection: Selection) -> Self {
+        Self { clipboard: None, selection }
+    }
+
+    /// arboard の Clipboard を（未作成なら作って）返す
+    fn clipboard(&mut self) -> Result<&mut Clipboard> {
+        if self.clipboard.is_none() {
+            let clipboard =
+                Clipboard::new().context("failed to open clipboard")?;
+            self.clipboard = Some(clipboard);
+        }
+
+        Ok(self.clipboard.as_mut().expect("clipboard was just opened"))
+    }
+}
+
+impl ClipboardSink for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        let selection = self.selection;
+        write_text(self.clipboard()?, selection, text)
+            .context("failed to set clipboard text")
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        let selection = self.selection;
+        write_text(self.clipboard()?, selection, "")
+            .context("failed to clear clipboard")
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<()> {
+        let selection = self.selection;
+        write_html(self.clipboard()?, selection, html, alt_text)
+            .context("failed to set clipboard html")
+    }
+
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>> {
+        let selection = self.selection;
+        Ok(read_text(self.clipboard()?, selection)
+            .filter(|text| !text.is_empty()))
+    }
+
+    // clipboard を OS が持つ Windows / macOS では何もしなくてよい
+    fn persist(&mut self, text: &str, timeout: Duration) -> Result<()> {
+        if cfg!(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        )) {
+            super::spawn_hold(text, timeout, self.selection)?;
+        }
+
+        Ok(())
+    }
+}
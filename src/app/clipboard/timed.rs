@@ -0,0 +1,67 @@
+// ============================================================================
+// src/app/clipboard/timed.rs
+// ============================================================================
+//
+// 最後に clipboard に書き込んだ時刻を覚えておくラッパ。
+//
+// --clear-after N では、取り込んでから N 秒たった clipboard をクリアする。
+// App の書き込みはヘッダ・フラグメント・フッタ・前回内容などいくつもの
+// 経路があるため、取り込み先そのものを包んで書き込みの時刻を記録する。
+// ============================================================================
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use super::ClipboardSink;
+
+// -----------------------------------------------------------------------------
+// TimedClipboard
+// -----------------------------------------------------------------------------
+pub struct TimedClipboard {
+    inner: Box<dyn ClipboardSink>,
+    written_at: Option<Instant>,
+}
+
+impl TimedClipboard {
+    pub fn new(inner: Box<dyn ClipboardSink>) -> Self {
+        Self { inner, written_at: None }
+    }
+
+    /// 最後に空でない内容を書き込んだ時刻（クリア後は None）
+    pub fn written_at(&self) -> Option<Instant> {
+        self.written_at
+    }
+}
+
+impl ClipboardSink for TimedClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.inner.set_text(text)?;
+        self.written_at = (!text.is_empty()).then(Instant::now);
+        Ok(())
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<()> {
+        self.inner.set_html(html, alt_text)?;
+        self.written_at = (!alt_text.is_empty()).then(Instant::now);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.inner.clear()?;
+        self.written_at = None;
+        Ok(())
+    }
+
+    fn can_read(&self) -> bool {
+        self.inner.can_read()
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>> {
+        self.inner.get_text()
+    }
+
+    fn persist(&mut self, text: &str, timeout: Duration) -> Result<()> {
+        self.inner.persist(text, timeout)
+    }
+}
@@ -0,0 +1,152 @@
+// ============================================================================
+// src/app/clipboard/verify.rs
+// ============================================================================
+//
+// clipboard への書き込みの確認と再試行。
+//
+// Linux では clipboard マネージャや他のアプリとの取り合いで、大きな書き込みが
+// 捨てられたり途中で切れたりすることがある。書き込んだ後に読み戻して
+// ハッシュを比べ、一致しなければ間隔を空けて（50ms → 100ms → …）書き直す。
+//
+// 読み戻せない取り込み先（外部コマンド・OSC 52・serve）は確認しない。
+// ============================================================================
+
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use super::ClipboardSink;
+
+/// 書き込みを試す回数（最初の 1 回を含む）
+pub const VERIFY_ATTEMPTS: u32 = 3;
+
+/// 1 回目の再試行までの待ち時間（以降は倍にする）
+const BACKOFF_BASE: Duration = Duration::from_millis(50);
+
+// -----------------------------------------------------------------------------
+// ContentMismatch
+// -----------------------------------------------------------------------------
+//
+// 再試行しても、読み戻した内容が書き込んだ内容と一致しなかったことを表す
+// エラー。App はこれを受け取るとプロンプトで知らせ、先へは進まない。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentMismatch {
+    /// 書き込んだバイト数
+    pub expected_bytes: usize,
+
+    /// 最後に読み戻したバイト数（読めなかった場合は 0）
+    pub actual_bytes: usize,
+}
+
+impl fmt::Display for ContentMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "clipboard の内容が取り込んだ内容と一致しません（{} 回試行、{} bytes 中 {} bytes）",
+            VERIFY_ATTEMPTS, self.expected_bytes, self.actual_bytes
+        )
+    }
+}
+
+impl std::error::Error for ContentMismatch {}
+
+// -----------------------------------------------------------------------------
+// set_text_verified
+// -----------------------------------------------------------------------------
+//
+// sink に text を書き込み、読み戻して確かめる。
+// 書き込み・読み戻しの失敗も、一致しなかった場合と同じく再試行する。
+// 最後まで一致しなければ ContentMismatch（または最後のエラー）を返す。
+// -----------------------------------------------------------------------------
+pub fn set_text_verified<S>(sink: &mut S, text: &str) -> Result<()>
+where
+    S: ClipboardSink + ?Sized,
+{
+    write_verified(sink, text, |sink| sink.set_text(text))
+}
+
+// -----------------------------------------------------------------------------
+// set_html_verified
+// -----------------------------------------------------------------------------
+//
+// sink に HTML と代替テキストを書き込み、読み戻して確かめる。
+// テキストとして読み戻すと代替テキストが返るため、alt_text と比べる。
+// -----------------------------------------------------------------------------
+pub fn set_html_verified<S>(
+    sink: &mut S,
+    html: &str,
+    alt_text: &str,
+) -> Result<()>
+where
+    S: ClipboardSink + ?Sized,
+{
+    write_verified(sink, alt_text, |sink| sink.set_html(html, alt_text))
+}
+
+// -----------------------------------------------------------------------------
+// write_verified
+// -----------------------------------------------------------------------------
+//
+// write で書き込み、読み戻した内容が text と一致するまで再試行する。
+// -----------------------------------------------------------------------------
+fn write_verified<S>(
+    sink: &mut S,
+    text: &str,
+    mut write: impl FnMut(&mut S) -> Result<()>,
+) -> Result<()>
+where
+    S: ClipboardSink + ?Sized,
+{
+    if !sink.can_read() {
+        return write(sink);
+    }
+
+    let expected = content_hash(text);
+    let mut delay = BACKOFF_BASE;
+    let mut last_error = None;
+
+    for attempt in 0..VERIFY_ATTEMPTS {
+        if attempt > 0 {
+            std::thread::sleep(delay);
+            delay *= 2;
+        }
+
+        let actual = match write(sink).and_then(|()| sink.get_text()) {
+            Ok(actual) => actual.unwrap_or_default(),
+            Err(e) => {
+                last_error = Some(e);
+                continue;
+            }
+        };
+
+        if content_hash(&actual) == expected {
+            return Ok(());
+        }
+
+        last_error = Some(
+            ContentMismatch {
+                expected_bytes: text.len(),
+                actual_bytes: actual.len(),
+            }
+            .into(),
+        );
+    }
+
+    Err(last_error.expect("VERIFY_ATTEMPTS is at least 1"))
+}
+
+// -----------------------------------------------------------------------------
+// content_hash
+// -----------------------------------------------------------------------------
+//
+// 内容の比較に使うハッシュ（64 ビットの FNV-1a）。
+// -----------------------------------------------------------------------------
+pub fn content_hash(text: &str) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    text.bytes()
+        .fold(OFFSET, |hash, b| (hash ^ u64::from(b)).wrapping_mul(PRIME))
+}
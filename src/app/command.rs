@@ -0,0 +1,651 @@
+// ============================================================================
+// src/app/command.rs
+// ============================================================================
+//
+// このファイルでは、プロンプトに対するユーザ入力を解釈して
+// Command 列挙型に変換する処理を提供する。
+//
+// main_loop / finalize_loop / exit_loop は、引数付きのコマンド（"b 2" や
+// "g 5" など）も含めて、ここで解釈した Command を受け取る。
+//
+// どのコマンドが有効かはループごとに異なるため、ここでは解釈だけを行い、
+// 受け付けるかどうかの判断は呼び出し側（App）に任せる。
+//
+// コマンドに割り当てるキーは KeyMap で変更できる。
+// 全画面 TUI モードのキーは、別の TuiKeyMap（[keys.tui]）で変更できる。
+// ============================================================================
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use super::state::Unit;
+
+// -----------------------------------------------------------------------------
+// Command
+// -----------------------------------------------------------------------------
+//
+// ユーザが入力できるコマンド。
+// フラグメント番号はプロンプト表示と同じく 1 始まり。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// 次のデータを clipboard に取り込む（y / yes）
+    Yes,
+
+    /// 直前のデータを再度 clipboard に取り込む（p / prev）
+    Prev,
+
+    /// clipboard を起動前の内容に戻して終了する（q / quit）
+    Quit,
+
+    /// 終了後も clipboard の内容を残して終了する（k / keep）
+    Keep,
+
+    /// 取り込まずに次のフラグメントへ進む（s / skip）
+    Skip,
+
+    /// 提示中のフラグメント全体を $PAGER で表示する（v / view）
+    View,
+
+    /// 提示中のフラグメントを $EDITOR で編集してから取り込む（e / edit）
+    Edit,
+
+    /// N 個前のフラグメントに戻って取り込む（b [N] / back [N]）
+    Back(usize),
+
+    /// N 個先のフラグメントに進んで取り込む（f [N] / forward [N]）
+    Forward(usize),
+
+    /// K 番目のフラグメントに移動して取り込む（g K / goto K / K）
+    Goto(usize),
+
+    /// 先頭のフラグメントに移動して取り込む（first / <）
+    First,
+
+    /// 最後のフラグメントに移動して取り込む（last / >）
+    Last,
+
+    /// 最大データ量・単位を変更し、現在位置から分割し直す
+    /// （m N [c|b] / max N [chars|bytes] / m c|b）
+    Resize { max_unit: Option<usize>, unit: Option<Unit> },
+
+    /// 提示中のフラグメントの末尾に N 行追加する（+ [N]）
+    Grow(usize),
+
+    /// 提示中のフラグメントの末尾から N 行削る（- [N]）
+    Shrink(usize),
+
+    /// 提示中のフラグメントを次の空行まで広げる（]）
+    NextBlank,
+
+    /// 提示中のフラグメントを前の空行まで縮める（[）
+    PrevBlank,
+
+    /// 入力データを正規表現で検索し、一致したフラグメントへ移動する（/pattern）
+    Search(String),
+
+    /// コマンド一覧を表示する（? / h / help）
+    Help,
+}
+
+impl Command {
+    /// フラグメント間を移動するコマンド（Back / Forward / Goto / First / Last）か
+    pub fn is_navigation(&self) -> bool {
+        matches!(
+            self,
+            Command::Back(_)
+                | Command::Forward(_)
+                | Command::Goto(_)
+                | Command::First
+                | Command::Last
+        )
+    }
+}
+
+// -----------------------------------------------------------------------------
+// KeyMap
+// -----------------------------------------------------------------------------
+//
+// 各コマンドに割り当てるキー（入力文字列）の一覧。
+// 設定ファイルの [keys] セクションで上書きできる（config.rs）。
+//
+//   [keys]
+//   yes  = ["n", "space"]
+//   back = ["b", "left"]
+//
+// 1 キー入力モードでは、スペースと矢印キーはそれぞれ
+// "space" / "left" / "right" / "up" / "down" という名前で届く。
+//
+// search は前置きのキーで、後ろに続く文字列を検索パターンとして扱う
+// （"/fn main" など）。同じキーを複数のコマンドに割り当てることはできない
+// （validate）。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyMap {
+    pub yes: Vec<String>,
+    pub prev: Vec<String>,
+    pub quit: Vec<String>,
+    pub keep: Vec<String>,
+    pub skip: Vec<String>,
+    pub view: Vec<String>,
+    pub edit: Vec<String>,
+    pub back: Vec<String>,
+    pub forward: Vec<String>,
+    pub goto: Vec<String>,
+    pub first: Vec<String>,
+    pub last: Vec<String>,
+    pub max: Vec<String>,
+    pub grow: Vec<String>,
+    pub shrink: Vec<String>,
+    pub next_blank: Vec<String>,
+    pub prev_blank: Vec<String>,
+    pub help: Vec<String>,
+    pub search: Vec<String>,
+
+    /// 全画面 TUI モードのキー割り当て（[keys.tui]）
+    pub tui: TuiKeyMap,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let keys =
+            |list: &[&str]| list.iter().map(|k| k.to_string()).collect();
+
+        Self {
+            yes: keys(&["y", "yes", "space"]),
+            prev: keys(&["p", "prev"]),
+            quit: keys(&["q", "quit"]),
+            keep: keys(&["k", "keep"]),
+            skip: keys(&["s", "skip"]),
+            view: keys(&["v", "view"]),
+            edit: keys(&["e", "edit"]),
+            back: keys(&["b", "back", "left", "up"]),
+            forward: keys(&["f", "forward", "right", "down"]),
+            goto: keys(&["g", "goto"]),
+            first: keys(&["first", "<"]),
+            last: keys(&["last", ">"]),
+            max: keys(&["m", "max"]),
+            grow: keys(&["+"]),
+            shrink: keys(&["-"]),
+            next_blank: keys(&["]"]),
+            prev_blank: keys(&["["]),
+            help: keys(&["?", "h", "help"]),
+            search: keys(&["/"]),
+            tui: TuiKeyMap::default(),
+        }
+    }
+}
+
+impl KeyMap {
+    // -------------------------------------------------------------------------
+    // KeyMap::line_starters
+    // -------------------------------------------------------------------------
+    //
+    // 1 キー入力モードで「続きを行入力で読む」べき先頭キー。
+    // 検索（search）と、引数が必須のコマンド（goto / max）の 1 文字キー。
+    // 数字は常に行入力になる（tty.rs の key_to_input）。
+    // -------------------------------------------------------------------------
+    pub fn line_starters(&self) -> Vec<char> {
+        self.search
+            .iter()
+            .chain(&self.goto)
+            .chain(&self.max)
+            .filter_map(|k| {
+                let mut chars = k.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c.to_ascii_lowercase()),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    // -------------------------------------------------------------------------
+    // KeyMap::validate
+    // -------------------------------------------------------------------------
+    //
+    // 同じキー（大文字・小文字は区別しない）が複数のコマンドに割り当てられて
+    // いないか、空のキーがないかを確かめる。TUI のキー割り当ても確かめる。
+    // -------------------------------------------------------------------------
+    pub fn validate(&self) -> Result<()> {
+        let bindings: [(&str, &[String]); 19] = [
+            ("yes", &self.yes),
+            ("prev", &self.prev),
+            ("quit", &self.quit),
+            ("keep", &self.keep),
+            ("skip", &self.skip),
+            ("view", &self.view),
+            ("edit", &self.edit),
+            ("back", &self.back),
+            ("forward", &self.forward),
+            ("goto", &self.goto),
+            ("first", &self.first),
+            ("last", &self.last),
+            ("max", &self.max),
+            ("grow", &self.grow),
+            ("shrink", &self.shrink),
+            ("next_blank", &self.next_blank),
+            ("prev_blank", &self.prev_blank),
+            ("help", &self.help),
+            ("search", &self.search),
+        ];
+        check_unique("keys", &bindings, str::to_lowercase)?;
+
+        self.tui.validate()
+    }
+
+    // -------------------------------------------------------------------------
+    // KeyMap::help_text
+    // -------------------------------------------------------------------------
+    //
+    // ? / help で表示するコマンド一覧を、現在のキー割り当てから作る。
+    // -------------------------------------------------------------------------
+    pub fn help_text(&self) -> String {
+        let entries: [(&[String], &str, &str); 18] = [
+            (&self.yes, "", "次のフラグメントを取り込む（Enter のみでも可）"),
+            (&self.prev, "", "直前に取り込んだデータを再度取り込む"),
+            (&self.skip, "", "取り込まずに次のフラグメントへ進む"),
+            (&self.view, "", "提示中のフラグメント全体を $PAGER で表示する"),
+            (
+                &self.edit,
+                "",
+                "提示中のフラグメントを $EDITOR で編集してから取り込む",
+            ),
+            (
+                &self.back,
+                " [N]",
+                "N 個前のフラグメントに戻って取り込む（既定 1）",
+            ),
+            (
+                &self.forward,
+                " [N]",
+                "N 個先のフラグメントに進んで取り込む（既定 1）",
+            ),
+            (&self.goto, " K / K", "K 番目のフラグメントに移動して取り込む"),
+            (&self.first, "", "先頭のフラグメントに移動して取り込む"),
+            (&self.last, "", "最後のフラグメントに移動して取り込む"),
+            (
+                &self.max,
+                " N [c|b]",
+                "最大データ量（と単位）を変更し、現在位置から分割し直す",
+            ),
+            (
+                &self.grow,
+                " [N]",
+                "提示中のフラグメントの末尾に N 行足す（既定 1）",
+            ),
+            (
+                &self.shrink,
+                " [N]",
+                "提示中のフラグメントの末尾から N 行削る（既定 1）",
+            ),
+            (
+                &self.next_blank,
+                "",
+                "提示中のフラグメントの末尾を次の空行まで広げる",
+            ),
+            (
+                &self.prev_blank,
+                "",
+                "提示中のフラグメントの末尾を前の空行まで縮める",
+            ),
+            (&self.quit, "", "clipboard を起動前の内容に戻して終了する"),
+            (
+                &self.keep,
+                "",
+                "clipboard の内容を残したまま終了する（最後のプロンプトのみ）",
+            ),
+            (&self.help, "", "このヘルプを表示する"),
+        ];
+
+        let mut out = String::new();
+        for (keys, args, description) in entries {
+            if keys.is_empty() {
+                continue;
+            }
+            let label = format!("{}{}", keys.join(", "), args);
+            out.push_str(&format!("  {:<20} {}\n", label, description));
+        }
+        if !self.search.is_empty() {
+            let label: Vec<String> =
+                self.search.iter().map(|k| format!("{}PATTERN", k)).collect();
+            out.push_str(&format!(
+                "  {:<20} {}\n",
+                label.join(", "),
+                "正規表現で検索し、一致したフラグメントへ移動する"
+            ));
+        }
+
+        out
+    }
+}
+
+// -----------------------------------------------------------------------------
+// parse_command
+// -----------------------------------------------------------------------------
+//
+// 既定のキー割り当て（KeyMap::default）で入力文字列を Command に変換する。
+// -----------------------------------------------------------------------------
+pub fn parse_command(input: &str) -> Option<Command> {
+    parse_command_with(input, &KeyMap::default())
+}
+
+// -----------------------------------------------------------------------------
+// parse_command_with
+// -----------------------------------------------------------------------------
+//
+// 入力文字列を、keys のキー割り当てに従って Command に変換する。
+// 大文字・小文字は区別しない。解釈できない場合は None を返す。
+//
+// 空入力時のデフォルト（"y" や "q"）は呼び出し側で補ってから渡すこと。
+// -----------------------------------------------------------------------------
+pub fn parse_command_with(input: &str, keys: &KeyMap) -> Option<Command> {
+    // 検索パターンは大文字・小文字を区別するため、小文字化の前に取り出す
+    // （複数の search キーが当てはまる場合は最も長いキーを採用する）
+    let trimmed = input.trim();
+    if let Some(pattern) = keys
+        .search
+        .iter()
+        .filter(|k| !k.is_empty())
+        .filter_map(|k| trimmed.strip_prefix(k.as_str()))
+        .min_by_key(|pattern| pattern.len())
+    {
+        return (!pattern.is_empty())
+            .then(|| Command::Search(pattern.to_string()));
+    }
+
+    let input = input.trim().to_lowercase();
+
+    // 数字のみの入力は「K 番目へ移動」とみなす
+    // （"+3" も usize として解釈できてしまうため、数字だけかを先に確かめる）
+    if !input.is_empty() && input.bytes().all(|b| b.is_ascii_digit()) {
+        return input.parse().ok().map(Command::Goto);
+    }
+
+    // ------------------------------------------------------------
+    // 引数を取らないコマンド（キーと完全一致）
+    // ------------------------------------------------------------
+    let exact = [
+        (&keys.yes, Command::Yes),
+        (&keys.prev, Command::Prev),
+        (&keys.quit, Command::Quit),
+        (&keys.keep, Command::Keep),
+        (&keys.skip, Command::Skip),
+        (&keys.view, Command::View),
+        (&keys.edit, Command::Edit),
+        (&keys.first, Command::First),
+        (&keys.last, Command::Last),
+        (&keys.next_blank, Command::NextBlank),
+        (&keys.prev_blank, Command::PrevBlank),
+        (&keys.help, Command::Help),
+    ];
+    for (aliases, command) in exact {
+        if aliases.iter().any(|k| k.to_lowercase() == input) {
+            return Some(command);
+        }
+    }
+
+    // ------------------------------------------------------------
+    // 引数を取るコマンド（キー + 引数）
+    // "b2" のような詰めた書き方も許す。
+    // ------------------------------------------------------------
+    let count = |arg: &str| -> Option<usize> {
+        if arg.is_empty() {
+            Some(1)
+        } else {
+            arg.parse().ok()
+        }
+    };
+
+    if let Some(arg) = strip_key(&keys.back, &input) {
+        return count(arg).map(Command::Back);
+    }
+    if let Some(arg) = strip_key(&keys.forward, &input) {
+        return count(arg).map(Command::Forward);
+    }
+    if let Some(arg) = strip_key(&keys.grow, &input) {
+        return count(arg).map(Command::Grow);
+    }
+    if let Some(arg) = strip_key(&keys.shrink, &input) {
+        return count(arg).map(Command::Shrink);
+    }
+    if let Some(arg) = strip_key(&keys.goto, &input) {
+        return arg.parse().ok().map(Command::Goto);
+    }
+    if let Some(arg) = strip_key(&keys.max, &input) {
+        return parse_resize(arg);
+    }
+
+    None
+}
+
+// -----------------------------------------------------------------------------
+// strip_key
+// -----------------------------------------------------------------------------
+//
+// input が keys のいずれかで始まり、その後ろが「空」または「数字・空白で
+// 始まる」場合に、後ろの部分（引数）を trim して返す。
+// 複数のキーが当てはまる場合は最も長いキーを採用する。
+// -----------------------------------------------------------------------------
+fn strip_key<'a>(keys: &[String], input: &'a str) -> Option<&'a str> {
+    keys.iter()
+        .map(|k| k.to_lowercase())
+        .filter(|k| !k.is_empty())
+        .filter_map(|k| {
+            let rest = input.strip_prefix(k.as_str())?;
+            let ok = rest
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_ascii_digit() || c.is_whitespace());
+            ok.then_some((k.len(), rest.trim()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, rest)| rest)
+}
+
+// -----------------------------------------------------------------------------
+// parse_resize
+// -----------------------------------------------------------------------------
+//
+// m / max コマンドの引数を解釈する。
+//   "20000"        → 最大データ量のみ変更
+//   "8000 b"       → 最大データ量と単位を変更
+//   "bytes"        → 単位のみ変更
+// -----------------------------------------------------------------------------
+fn parse_resize(arg: &str) -> Option<Command> {
+    let mut max_unit = None;
+    let mut unit = None;
+
+    for token in arg.split_whitespace() {
+        match token {
+            "c" | "chars" => unit = Some(Unit::Chars),
+            "b" | "bytes" => unit = Some(Unit::Bytes),
+            _ => max_unit = Some(token.replace('_', "").parse().ok()?),
+        }
+    }
+
+    // 0 は「何も取り込めない」ので受け付けない
+    if max_unit == Some(0) || (max_unit.is_none() && unit.is_none()) {
+        return None;
+    }
+
+    Some(Command::Resize { max_unit, unit })
+}
+
+// -----------------------------------------------------------------------------
+// TuiAction
+// -----------------------------------------------------------------------------
+//
+// 全画面 TUI モードのキー操作。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuiAction {
+    /// 選択中のフラグメントを取り込み、次を選択する
+    Copy,
+    /// 取り込まずに次へ
+    Skip,
+    /// 選択を 1 つ下へ
+    Down,
+    /// 選択を 1 つ上へ
+    Up,
+    /// 先頭のフラグメントを選択する
+    First,
+    /// 最後のフラグメントを選択する
+    Last,
+    /// プレビューを下へスクロールする
+    ScrollDown,
+    /// プレビューを上へスクロールする
+    ScrollUp,
+    /// 検索パターンの入力を始める
+    Search,
+    /// フッタを取り込む（ファイル指定時のみ）
+    Footer,
+    /// 終了する
+    Quit,
+}
+
+// -----------------------------------------------------------------------------
+// TuiKeyMap
+// -----------------------------------------------------------------------------
+//
+// 全画面 TUI モードのキー割り当て。設定ファイルの [keys.tui] で上書きできる。
+//
+//   [keys.tui]
+//   down = ["down", "j"]
+//   quit = ["q"]
+//
+// 文字キーは大文字・小文字を区別する（"g" と "G" は別のキー）。
+// 文字以外のキーは "enter" / "esc" / "space" / "tab" / "backspace" /
+// "up" / "down" / "left" / "right" / "home" / "end" / "pageup" / "pagedown"
+// という名前で指定する。Ctrl-C は常に終了する。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuiKeyMap {
+    pub copy: Vec<String>,
+    pub skip: Vec<String>,
+    pub down: Vec<String>,
+    pub up: Vec<String>,
+    pub first: Vec<String>,
+    pub last: Vec<String>,
+    pub scroll_down: Vec<String>,
+    pub scroll_up: Vec<String>,
+    pub search: Vec<String>,
+    pub footer: Vec<String>,
+    pub quit: Vec<String>,
+}
+
+impl Default for TuiKeyMap {
+    fn default() -> Self {
+        let keys =
+            |list: &[&str]| list.iter().map(|k| k.to_string()).collect();
+
+        Self {
+            copy: keys(&["enter", "c", "y"]),
+            skip: keys(&["s"]),
+            down: keys(&["down", "j", "n"]),
+            up: keys(&["up", "k", "p"]),
+            first: keys(&["home", "g"]),
+            last: keys(&["end", "G"]),
+            scroll_down: keys(&["pagedown", "space"]),
+            scroll_up: keys(&["pageup"]),
+            search: keys(&["/"]),
+            footer: keys(&["F"]),
+            quit: keys(&["q", "esc"]),
+        }
+    }
+}
+
+impl TuiKeyMap {
+    fn bindings(&self) -> [(&'static str, &[String], TuiAction); 11] {
+        [
+            ("copy", &self.copy, TuiAction::Copy),
+            ("skip", &self.skip, TuiAction::Skip),
+            ("down", &self.down, TuiAction::Down),
+            ("up", &self.up, TuiAction::Up),
+            ("first", &self.first, TuiAction::First),
+            ("last", &self.last, TuiAction::Last),
+            ("scroll_down", &self.scroll_down, TuiAction::ScrollDown),
+            ("scroll_up", &self.scroll_up, TuiAction::ScrollUp),
+            ("search", &self.search, TuiAction::Search),
+            ("footer", &self.footer, TuiAction::Footer),
+            ("quit", &self.quit, TuiAction::Quit),
+        ]
+    }
+
+    /// キー名（"j" / "G" / "enter" など）に割り当てた操作
+    pub fn action(&self, key: &str) -> Option<TuiAction> {
+        self.bindings()
+            .into_iter()
+            .find(|(_, keys, _)| keys.iter().any(|k| k == key))
+            .map(|(_, _, action)| action)
+    }
+
+    // -------------------------------------------------------------------------
+    // TuiKeyMap::help_line
+    // -------------------------------------------------------------------------
+    //
+    // 画面下部に表示するキー操作の案内（"enter/c/y:copy  s:skip  …"）。
+    // 現在の割り当てから作る。キーのない操作は表示しない。
+    // -------------------------------------------------------------------------
+    pub fn help_line(&self) -> String {
+        self.bindings()
+            .into_iter()
+            .filter(|(_, keys, _)| !keys.is_empty())
+            .map(|(name, keys, _)| format!("{}:{}", keys.join("/"), name))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// 同じキーが複数の操作に割り当てられていないかを確かめる
+    pub fn validate(&self) -> Result<()> {
+        let bindings: Vec<(&str, &[String])> = self
+            .bindings()
+            .into_iter()
+            .map(|(name, keys, _)| (name, keys))
+            .collect();
+
+        check_unique("keys.tui", &bindings, str::to_string)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// check_unique
+// -----------------------------------------------------------------------------
+//
+// bindings（コマンド名とキーの一覧）で、normalize したキーが複数の
+// コマンドに割り当てられていればエラーにする。空のキーもエラー。
+// section はエラーメッセージに使う設定ファイルのセクション名。
+// -----------------------------------------------------------------------------
+fn check_unique(
+    section: &str,
+    bindings: &[(&str, &[String])],
+    normalize: impl Fn(&str) -> String,
+) -> Result<()> {
+    let mut owners: HashMap<String, &str> = HashMap::new();
+
+    for &(name, keys) in bindings {
+        for key in keys {
+            let key = normalize(key);
+            if key.is_empty() {
+                bail!("[{}] の {} に空のキーがあります", section, name);
+            }
+
+            match owners.insert(key.clone(), name) {
+                Some(other) if other != name => bail!(
+                    "[{}] のキー \"{}\" が {} と {} の両方に割り当てられています",
+                    section,
+                    key,
+                    other,
+                    name
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+// ============================================================================
+// src/app/config.rs
+// ============================================================================
+//
+// このファイルでは、設定ファイル（TOML）から読み込む設定を定義する。
+//
+// 設定ファイルの場所：
+//
+//   - --config PATH で指定されたファイル
+//   - なければ $XDG_CONFIG_HOME/clip_frag/config.toml
+//     （XDG_CONFIG_HOME が未設定なら ~/.config/clip_frag/config.toml、
+//       Windows では %APPDATA%\clip_frag\config.toml）
+//
+// 既定の場所にファイルがなければ、すべて既定値で動く。
+// 設定できる項目（すべて省略可）：
+//
+//   [keys]                      # コマンドのキー割り当て（command.rs の KeyMap）
+//   yes  = ["n", "space"]
+//   back = ["b", "left"]
+//   search = ["/"]
+//
+//   [keys.tui]                  # 全画面 TUI モードのキー割り当て（TuiKeyMap）
+//   down = ["down", "j"]
+//
+// 同じキーを複数のコマンドに割り当てるとエラーになる。
+//
+//   [prompts]                   # プロンプトの文言
+//   main = "[{index}/{count}] {range} ({percent} %) > "
+//   exit = "p/q > "
+//
+//   [clipboard]                 # 取り込み先（--clipboard が優先）
+//   backend = "wl-copy"
+//   selection = "both"          # CLIPBOARD / PRIMARY（--selection が優先）
+//   hold_timeout = 600          # K(eep) で内容を残す秒数（--hold-timeout が優先）
+//   clear_on_exit = true        # Quit で元の内容に戻さずクリアする
+//   clear_after = 60            # 取り込んでから 60 秒でクリア（--clear-after）
+//   idle_timeout = 900          # 900 秒操作がなければ終了（--idle-timeout）
+//   html = true                 # HTML で取り込む（--html）
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::clipboard::{ClipboardBackend, Selection};
+use super::command::KeyMap;
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// コマンドのキー割り当て
+    pub keys: KeyMap,
+
+    /// プロンプトの文言
+    pub prompts: Prompts,
+
+    /// clipboard まわりの設定
+    pub clipboard: ClipboardConfig,
+}
+
+impl Config {
+    // -------------------------------------------------------------------------
+    // Config::load
+    // -------------------------------------------------------------------------
+    //
+    // path が指定されていればそのファイルを読む（存在しなければエラー）。
+    // 指定がなければ既定の場所を探し、なければ既定値を返す。
+    // -------------------------------------------------------------------------
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::from_file(path),
+            None => match default_config_path() {
+                Some(path) if path.is_file() => Self::from_file(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// TOML ファイルを読み込んで Config にする
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| {
+            format!("設定ファイルを読み込めません: {}", path.display())
+        })?;
+
+        Self::from_toml(&text).with_context(|| {
+            format!("設定ファイルの形式が不正です: {}", path.display())
+        })
+    }
+
+    /// TOML 文字列を Config にする（キー割り当ての重複はエラー）
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text)?;
+        config.keys.validate()?;
+        Ok(config)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Prompts
+// -----------------------------------------------------------------------------
+//
+// 各ループのプロンプト文言。{name} の形のプレースホルダを埋め込める。
+//
+//   main で使えるもの：
+//     {index} {count}         提示中のフラグメント番号 / フラグメント数
+//     {range}                 行範囲（L120–L348）
+//     {units} {unit}          フラグメントの単位数 / 単位名（chars, bytes）
+//     {percent}               フラグメントの割合（小数 1 桁）
+//     {cumulative} {total}    取り込み後の累積単位数 / 全体の単位数
+//     {cumulative_percent}    累積の割合（小数 1 桁）
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prompts {
+    /// main_loop のプロンプト
+    pub main: String,
+
+    /// finalize_loop（フッタ取り込み確認）のプロンプト
+    pub footer: String,
+
+    /// exit_loop のプロンプト
+    pub exit: String,
+
+    /// main_loop / finalize_loop で解釈できない入力があったときの表示
+    pub invalid: String,
+
+    /// exit_loop で解釈できない入力があったときの表示
+    pub invalid_exit: String,
+}
+
+impl Default for Prompts {
+    fn default() -> Self {
+        Self {
+            main: "[{index}/{count}] {range} +{units} [{unit}] ({percent} %), {cumulative} / {total} ({cumulative_percent} %): Y(es)/S(kip)/P(rev)/V(iew)/E(dit)/Q(uit)/?(help) [y]: ".to_string(),
+            footer: "+footer prompt: Y(es)/P(rev)/Q(uit)/?(help) [y]: "
+                .to_string(),
+            exit: "P(rev)/K(eep)/Q(uit)/?(help) [q]: ".to_string(),
+            invalid: "無効な入力です。Y(es)/P(rev)/Q(uit) を入力してください（? でヘルプ）。".to_string(),
+            invalid_exit: "無効な入力です。P(rev)/K(eep)/Q(uit) を入力してください（? でヘルプ）。".to_string(),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ClipboardConfig
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+    /// 取り込み先（"system" / "wl-copy" / "command:CMD" / "file:PATH" など）
+    pub backend: Option<ClipboardBackend>,
+
+    /// arboard での書き込み先（"clipboard" / "primary" / "both"）
+    pub selection: Option<Selection>,
+
+    /// K(eep) で終了した後、内容を残しておく秒数（既定 600）
+    pub hold_timeout: Option<u64>,
+
+    /// Quit で起動前の内容に戻さず、clipboard をクリアする
+    pub clear_on_exit: bool,
+
+    /// 取り込んでからこの秒数がたったら clipboard をクリアする
+    pub clear_after: Option<u64>,
+
+    /// この秒数のあいだ入力がなければ、Quit と同じく終了する
+    pub idle_timeout: Option<u64>,
+
+    /// フラグメントを HTML（構文ハイライト付き）として取り込む
+    pub html: bool,
+}
+
+// -----------------------------------------------------------------------------
+// render_template
+// -----------------------------------------------------------------------------
+//
+// template 中の {name} を vars の値で置き換える。
+// vars にない {name} はそのまま残す。
+// -----------------------------------------------------------------------------
+pub fn render_template(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+// -----------------------------------------------------------------------------
+// default_config_path
+// -----------------------------------------------------------------------------
+//
+// 既定の設定ファイルの場所。ホームディレクトリが分からなければ None。
+// -----------------------------------------------------------------------------
+pub fn default_config_path() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from)
+    };
+
+    let base = if cfg!(windows) {
+        env_dir("APPDATA")?
+    } else {
+        env_dir("XDG_CONFIG_HOME")
+            .or_else(|| env_dir("HOME").map(|home| home.join(".config")))?
+    };
+
+    Some(base.join("clip_frag").join("config.toml"))
+}
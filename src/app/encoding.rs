@@ -5,53 +5,300 @@
 // このファイルでは、入力データのエンコードを判定し、UTF-8 の String に
 // デコードするための関数を提供する。
 //
-// clip_frag が扱う入力データは、以下の 2 種類を想定している：
+// clip_frag が扱う入力データは、以下のエンコードを想定している：
 //
-//   - UTF-8
+//   - UTF-8（BOM あり・なし）
 //   - Shift_JIS
+//   - EUC-JP（古いソースコードなど）
+//   - ISO-2022-JP（メールのダンプなど）
+//   - UTF-16LE / UTF-16BE（BOM あり・なし。Windows のツールの出力など）
 //
-// まず UTF-8 として解釈を試み、失敗した場合は Shift_JIS とみなす。
-// Shift_JIS としてもデコードできなければエラーとする。
+// 判定は以下の順に行う：
+//
+//   1. 先頭に BOM があれば、そのエンコードに決める（確度 100%）。
+//   2. ISO-2022-JP の漢字への切り替え（ESC $ B など）があり、
+//      ISO-2022-JP としてデコードできれば、ISO-2022-JP に決める。
+//      ISO-2022-JP は 7 ビットのため、UTF-8 などとしてもデコードできて
+//      しまい、採点では区別しにくい。
+//   3. NUL を含まず、UTF-8 として正しければ UTF-8 に決める（確度 100%）。
+//      UTF-8 の複数バイト文字の並びは偶然にはまず現れないため、
+//      アクセント付きのラテン文字・ギリシャ文字・絵文字なども含めて
+//      採点より確かである。
+//   4. それ以外は、候補のエンコードでそれぞれデコードを試み、
+//      デコードできたものを「日本語の文章としてのもっともらしさ」で
+//      採点して、最も点の高いものを選ぶ。
+//
+// 確度（confidence）は、選んだ候補の点が他の候補に比べてどれだけ
+// 抜きん出ているかを 0.0〜1.0 で表す。どの候補でもデコードできなければ
+// エラーとする。
 //
 // この処理は、ファイル入力・標準入力のどちらにも共通して使われる。
 // ============================================================================
 
 use anyhow::Result;
-use encoding_rs::SHIFT_JIS;
+use encoding_rs::{
+    Encoding, EUC_JP, ISO_2022_JP, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8,
+};
+
+/// BOM がない場合に試す 8 ビットのエンコード（同点なら先のものを選ぶ）
+const CANDIDATES: &[&Encoding] = &[UTF_8, SHIFT_JIS, EUC_JP];
+
+/// ISO-2022-JP で JIS X 0208 に切り替えるエスケープシーケンス
+const ISO_2022_JP_ESCAPES: &[&[u8]] = &[b"\x1b$B", b"\x1b$@"];
+
+/// BOM がない場合に試す UTF-16（偶数バイトの入力のみ）
+const UTF16_CANDIDATES: &[&Encoding] = &[UTF_16LE, UTF_16BE];
+
+/// 確度を求めるときに点を何乗するか（大きいほど差を強調する）
+const CONFIDENCE_SHARPNESS: i32 = 4;
 
 // -----------------------------------------------------------------------------
-// detect_encoding_and_decode
+// Detection
 // -----------------------------------------------------------------------------
 //
-// バイト列からエンコードを判定し、UTF-8 の String にデコードする。
+// エンコードの判定結果。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// デコードした文字列（BOM は取り除く）
+    pub text: String,
+
+    /// エンコード名（"UTF-8" / "Shift_JIS" / "EUC-JP" / "ISO-2022-JP" /
+    /// "UTF-16LE" / "UTF-16BE"）
+    pub encoding: &'static str,
+
+    /// 判定の確度（0.0〜1.0）
+    pub confidence: f64,
+
+    /// BOM で判定したか
+    pub bom: bool,
+}
+
+// -----------------------------------------------------------------------------
+// detect_encoding
+// -----------------------------------------------------------------------------
 //
-// 戻り値：
-//   (decoded_string, "UTF-8") または (decoded_string, "Shift_JIS")
+// バイト列からエンコードを判定し、UTF-8 の String にデコードする。
 //
 // エラー：
-//   UTF-8 としても Shift_JIS としても解釈できない場合。
+//   BOM のエンコードでデコードできない場合、
+//   または BOM がなく、どの候補でもデコードできない場合。
 // -----------------------------------------------------------------------------
-pub fn detect_encoding_and_decode(
-    buf: &[u8],
-) -> Result<(String, &'static str)> {
+pub fn detect_encoding(buf: &[u8]) -> Result<Detection> {
     // ------------------------------------------------------------
-    // 1. UTF-8 として解釈を試みる
+    // 1. BOM があれば、そのエンコードに決める
     // ------------------------------------------------------------
-    if let Ok(s) = String::from_utf8(buf.to_vec()) {
-        return Ok((s, "UTF-8"));
+    if let Some((encoding, bom_len)) = Encoding::for_bom(buf) {
+        let Some(text) = encoding
+            .decode_without_bom_handling_and_without_replacement(
+                &buf[bom_len..],
+            )
+        else {
+            anyhow::bail!(
+                "入力データのエンコード判定に失敗しました（{} の BOM がありますが、{} として解釈できません）",
+                encoding.name(),
+                encoding.name()
+            );
+        };
+
+        return Ok(Detection {
+            text: text.into_owned(),
+            encoding: encoding.name(),
+            confidence: 1.0,
+            bom: true,
+        });
+    }
+
+    // ------------------------------------------------------------
+    // 2. ISO-2022-JP のエスケープシーケンスがあれば ISO-2022-JP
+    // ------------------------------------------------------------
+    if has_iso_2022_jp_escape(buf) {
+        if let Some(text) = ISO_2022_JP
+            .decode_without_bom_handling_and_without_replacement(buf)
+        {
+            return Ok(Detection {
+                confidence: plausibility(&text),
+                text: text.into_owned(),
+                encoding: ISO_2022_JP.name(),
+                bom: false,
+            });
+        }
+    }
+
+    // ------------------------------------------------------------
+    // 3. NUL を含まない正しい UTF-8 なら UTF-8
+    //    NUL を含む場合は、ASCII を含む BOM なしの UTF-16 の
+    //    可能性があるため採点に回す。
+    // ------------------------------------------------------------
+    if !buf.contains(&0) {
+        if let Ok(text) = std::str::from_utf8(buf) {
+            return Ok(Detection {
+                text: text.to_string(),
+                encoding: UTF_8.name(),
+                confidence: 1.0,
+                bom: false,
+            });
+        }
+    }
+
+    // ------------------------------------------------------------
+    // 4. デコードできる候補を集める
+    //    UTF-16 は偶数バイトの入力だけ試す。NUL を含まない ASCII だけの
+    //    入力は、UTF-16 として読むと漢字の並びになり、もっともらしく
+    //    見えてしまうため試さない。
+    // ------------------------------------------------------------
+    let mut decoded: Vec<(&'static Encoding, String)> =
+        decode_candidates(buf, CANDIDATES);
+
+    let plain_ascii = buf.iter().all(|&b| b.is_ascii() && b != 0);
+    if buf.len().is_multiple_of(2) && !plain_ascii {
+        decoded.extend(decode_candidates(buf, UTF16_CANDIDATES));
     }
 
     // ------------------------------------------------------------
-    // 2. UTF-8 でなければ Shift_JIS とみなしてデコードする
+    // 5. 採点して最も点の高い候補を選ぶ（同点なら先の候補）
     // ------------------------------------------------------------
-    let (cow, _, had_errors) = SHIFT_JIS.decode(buf);
+    let scored: Vec<(&'static Encoding, String, f64)> = decoded
+        .into_iter()
+        .map(|(encoding, text)| {
+            let score = plausibility(&text);
+            (encoding, text, score)
+        })
+        .collect();
 
-    if had_errors {
-        // Shift_JIS としてもデコードに失敗した場合はエラー
+    let Some(best) = scored
+        .iter()
+        .enumerate()
+        .max_by(|(i, a), (j, b)| a.2.total_cmp(&b.2).then(j.cmp(i)))
+        .map(|(i, _)| i)
+    else {
         anyhow::bail!(
-            "入力データのエンコード判定に失敗しました（UTF-8 / Shift_JIS いずれでも解釈できません）"
+            "入力データのエンコード判定に失敗しました（UTF-8 / Shift_JIS / EUC-JP / ISO-2022-JP / UTF-16 いずれでも解釈できません）"
         );
+    };
+
+    let confidence = confidence(&scored, best);
+    let (encoding, text, _) = scored.into_iter().nth(best).unwrap();
+
+    Ok(Detection { text, encoding: encoding.name(), confidence, bom: false })
+}
+
+// -----------------------------------------------------------------------------
+// detect_encoding_and_decode
+// -----------------------------------------------------------------------------
+//
+// detect_encoding の結果のうち、デコードした文字列とエンコード名だけを返す。
+//
+// 戻り値：
+//   (decoded_string, "UTF-8") や (decoded_string, "Shift_JIS") など
+// -----------------------------------------------------------------------------
+pub fn detect_encoding_and_decode(
+    buf: &[u8],
+) -> Result<(String, &'static str)> {
+    let detection = detect_encoding(buf)?;
+
+    Ok((detection.text, detection.encoding))
+}
+
+/// ISO-2022-JP の漢字への切り替えを含むか
+fn has_iso_2022_jp_escape(buf: &[u8]) -> bool {
+    buf.windows(3).any(|w| ISO_2022_JP_ESCAPES.contains(&w))
+}
+
+/// candidates のうち、buf を置換文字なしでデコードできたものを返す
+fn decode_candidates(
+    buf: &[u8],
+    candidates: &[&'static Encoding],
+) -> Vec<(&'static Encoding, String)> {
+    candidates
+        .iter()
+        .filter_map(|&encoding| {
+            encoding
+                .decode_without_bom_handling_and_without_replacement(buf)
+                .map(|text| (encoding, text.into_owned()))
+        })
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+// plausibility
+// -----------------------------------------------------------------------------
+//
+// デコードした文字列の、日本語・英語の文章としてのもっともらしさ（0.0〜1.0）。
+// 1 文字ごとに点を付けて平均する。
+//
+// 誤ったエンコードでデコードすると、制御文字・半角カナ・まれな漢字などが
+// 混ざりやすいため、それらの点を低くしている。
+// -----------------------------------------------------------------------------
+fn plausibility(text: &str) -> f64 {
+    let (sum, count) = text.chars().fold((0.0, 0usize), |(sum, count), c| {
+        (sum + char_weight(c), count + 1)
+    });
+
+    if count == 0 {
+        1.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// 1 文字の点
+fn char_weight(c: char) -> f64 {
+    match c {
+        // 改行・タブ・ASCII の表示文字
+        '\t' | '\n' | '\r' | ' '..='~' => 1.0,
+
+        // ひらがな・カタカナ・全角記号・全角英数字
+        '\u{3000}'..='\u{30FF}' | '\u{FF01}'..='\u{FF5E}' => 1.0,
+
+        // 漢字（文字化けも漢字になりやすいため少し低く）
+        '\u{4E00}'..='\u{9FFF}' => 0.7,
+
+        // 記号・罫線・ラテン文字など
+        '\u{00A0}'..='\u{024F}'
+        | '\u{0370}'..='\u{04FF}'
+        | '\u{2000}'..='\u{27BF}' => 0.5,
+
+        // 半角カナ（文字化けでよく現れる）
+        '\u{FF61}'..='\u{FF9F}' => 0.3,
+
+        // 制御文字・私用領域・置換文字
+        '\u{0000}'..='\u{001F}'
+        | '\u{007F}'..='\u{009F}'
+        | '\u{E000}'..='\u{F8FF}'
+        | '\u{FFFD}' => 0.0,
+
+        _ => 0.2,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// confidence
+// -----------------------------------------------------------------------------
+//
+// scored[best] を選んだときの確度。
+//
+// 各候補の点を CONFIDENCE_SHARPNESS 乗して選んだ候補の割合を求め、
+// 選んだ候補の点を掛けたものを確度とする（どの候補ももっともらしくなければ
+// 確度も低くなる）。ASCII だけの入力のように、選んだ候補と同じ文字列になる
+// 候補は競合しないため数えない。
+// -----------------------------------------------------------------------------
+fn confidence(
+    scored: &[(&'static Encoding, String, f64)],
+    best: usize,
+) -> f64 {
+    let (_, best_text, best_score) = &scored[best];
+    let weight = |score: f64| score.powi(CONFIDENCE_SHARPNESS);
+
+    let rivals: f64 = scored
+        .iter()
+        .filter(|(_, text, _)| text != best_text)
+        .map(|(_, _, score)| weight(*score))
+        .sum();
+
+    if rivals == 0.0 {
+        return *best_score;
     }
 
-    Ok((cow.to_string(), "Shift_JIS"))
+    best_score * weight(*best_score) / (weight(*best_score) + rivals)
 }
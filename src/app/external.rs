@@ -0,0 +1,229 @@
+// ============================================================================
+// src/app/external.rs
+// ============================================================================
+//
+// このファイルでは、外部コマンド（ページャ・エディタ）との連携を提供する。
+//
+// 外部コマンドは環境変数で指定されたものをシェル経由で起動する。
+// "less -R" のように引数付きで指定されることが多いため、
+// 自前で分割せずにシェルに解釈させる。
+//
+//   - Unix 系 OS: sh -c "<command>"
+//   - Windows:    cmd /C "<command>"
+// ============================================================================
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// 一時ファイルの名前が衝突したときに作り直す回数
+const TEMP_FILE_ATTEMPTS: u32 = 16;
+
+// -----------------------------------------------------------------------------
+// open_in_pager
+// -----------------------------------------------------------------------------
+//
+// text を $PAGER の標準入力に流して表示する。ページャの終了まで待つ。
+// $PAGER が未設定の場合は less（Windows では more）を使う。
+// -----------------------------------------------------------------------------
+pub fn open_in_pager(text: &str) -> Result<()> {
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| default_pager().to_string());
+
+    let mut child = shell_command(&pager)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to launch pager: {}", pager))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // ページャが途中で終了した場合（q で閉じた等）の書き込みエラーは無視する
+        let _ = stdin.write_all(text.as_bytes());
+    }
+
+    child.wait().context("failed to wait for pager")?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// edit_in_editor
+// -----------------------------------------------------------------------------
+//
+// text を一時ファイルに書き出して $VISUAL / $EDITOR で開き、
+// エディタ終了後のファイル内容を返す。一時ファイルは読み込み後に削除する。
+//
+// extension を指定すると一時ファイルの拡張子にする
+// （エディタの構文ハイライトを効かせるため）。
+//
+// エディタが 0 以外で終了した場合はエラーとする。
+// -----------------------------------------------------------------------------
+pub fn edit_in_editor(text: &str, extension: Option<&str>) -> Result<String> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| default_editor().to_string());
+
+    let path = write_temp_file(text, extension)?;
+
+    let result = run_editor(&editor, &path).and_then(|_| {
+        std::fs::read_to_string(&path).with_context(|| {
+            format!("failed to read temp file: {}", path.display())
+        })
+    });
+
+    // 成否にかかわらず一時ファイルは削除する
+    let _ = std::fs::remove_file(&path);
+
+    result
+}
+
+// -----------------------------------------------------------------------------
+// run_editor
+// -----------------------------------------------------------------------------
+//
+// エディタを起動して終了を待つ。
+// 標準入力がパイプで埋まっている場合でも操作できるよう、
+// Unix 系 OS ではエディタの標準入力に /dev/tty をつなぐ。
+// -----------------------------------------------------------------------------
+fn run_editor(editor: &str, path: &Path) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        shell_command(&format!("{} \"{}\"", editor, path.display()))
+    } else {
+        // パスはシェルに解釈させず、位置引数 $1 として渡す
+        let mut cmd = shell_command(&format!("{} \"$1\"", editor));
+        cmd.arg("sh").arg(path);
+        cmd
+    };
+
+    #[cfg(unix)]
+    if let Ok(tty) = std::fs::File::open("/dev/tty") {
+        cmd.stdin(Stdio::from(tty));
+    }
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to launch editor: {}", editor))?;
+
+    if !status.success() {
+        anyhow::bail!("editor exited with {}", status);
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// write_temp_file
+// -----------------------------------------------------------------------------
+//
+// text を新しい一時ファイルに書き出し、そのパスを返す。
+//
+// フラグメントには秘密の情報が含まれることもあるため、
+//   - 既存のファイル（他のユーザが置いたシンボリックリンクなど）は開かず、
+//     必ず新しく作る（create_new）。名前が衝突したら別の名前で作り直す。
+//   - Unix 系 OS では本人だけが読み書きできるようにする（0600）。
+// -----------------------------------------------------------------------------
+fn write_temp_file(text: &str, extension: Option<&str>) -> Result<PathBuf> {
+    let mut attempt = 0;
+
+    loop {
+        let path = temp_file_path(extension, attempt);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        match options.open(&path) {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(text.as_bytes()) {
+                    let _ = std::fs::remove_file(&path);
+                    return Err(e).with_context(|| {
+                        format!(
+                            "failed to write temp file: {}",
+                            path.display()
+                        )
+                    });
+                }
+                return Ok(path);
+            }
+            Err(e)
+                if e.kind() == std::io::ErrorKind::AlreadyExists
+                    && attempt < TEMP_FILE_ATTEMPTS =>
+            {
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("failed to create temp file: {}", path.display())
+                })
+            }
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// temp_file_path
+// -----------------------------------------------------------------------------
+//
+// 他のプロセスと衝突しにくい一時ファイルのパスを作る。
+// attempt は作り直しの回数で、衝突した場合に別の名前にするために使う。
+// -----------------------------------------------------------------------------
+fn temp_file_path(extension: Option<&str>, attempt: u32) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let ext = extension.unwrap_or("txt");
+
+    std::env::temp_dir().join(format!(
+        "clip_frag-{}-{}-{}.{}",
+        std::process::id(),
+        nanos,
+        attempt,
+        ext
+    ))
+}
+
+// -----------------------------------------------------------------------------
+// default_pager / default_editor
+// -----------------------------------------------------------------------------
+fn default_editor() -> &'static str {
+    if cfg!(windows) {
+        "notepad"
+    } else {
+        "vi"
+    }
+}
+
+fn default_pager() -> &'static str {
+    if cfg!(windows) {
+        "more"
+    } else {
+        "less"
+    }
+}
+
+// -----------------------------------------------------------------------------
+// shell_command
+// -----------------------------------------------------------------------------
+//
+// command_line をシェル経由で実行する Command を作る。
+// -----------------------------------------------------------------------------
+pub(crate) fn shell_command(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command_line);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command_line);
+        cmd
+    }
+}
@@ -3,7 +3,12 @@
 // ============================================================================
 //
 // このファイルでは、clip_frag の「分割処理」の中心となるロジックを提供する。
-// 具体的には以下の 3 つの責務を持つ：
+// 具体的には以下の責務を持つ：
+//
+//   0. SplitStrategy / GreedyLineStrategy
+//      - 「どこでフラグメントを区切るか」を決める分割戦略のトレイトと、
+//        その標準実装（貪欲な行詰め込み）。
+//      - ライブラリ利用者は独自の SplitStrategy を実装して差し替えられる。
 //
 //   1. build_fragment()
 //      - curr_index から始めて、最大データ量を超えない範囲で行を詰め込む。
@@ -23,6 +28,99 @@
 
 use super::state::AppState;
 
+// -----------------------------------------------------------------------------
+// SplitStrategy
+// -----------------------------------------------------------------------------
+//
+// フラグメントの区切り位置（境界）を決める分割戦略。
+//
+// 入力は「行データ」「各行の単位数」「開始行 index」「最大データ量」のみで、
+// AppState には依存しない。これにより、clip_frag を利用する外部 crate が
+// ドメイン固有の分割（関数単位・段落単位など）を自由に実装できる。
+//
+// 実装者が用意するのは next_boundary() だけでよい。
+// plan_boundaries() は next_boundary() を繰り返し呼ぶ既定実装を持つ。
+// -----------------------------------------------------------------------------
+pub trait SplitStrategy {
+    /// start_index から始まるフラグメントの終端（次の開始行 index）を返す。
+    ///
+    /// 戻り値は start_index 以上 lines.len() 以下であること。
+    fn next_boundary(
+        &self,
+        lines: &[String],
+        line_units: &[usize],
+        start_index: usize,
+        max_unit: usize,
+    ) -> usize;
+
+    /// start_index から末尾までのすべての境界（各フラグメントの終端）を返す。
+    ///
+    /// 戻り値は start_index より大きい狭義単調増加の列で、すべて
+    /// lines.len() 以下、最後の要素は lines.len() であること
+    /// （start_index が末尾なら空）。守られていない場合、FragmentPlan は
+    /// 分割計画を作らずにエラーを返す。
+    ///
+    /// next_boundary() が前に進まない場合（1 行だけで max_unit を超える等）は、
+    /// 無限ループを避けるため、その行 1 行だけを 1 フラグメントとして扱う。
+    fn plan_boundaries(
+        &self,
+        lines: &[String],
+        line_units: &[usize],
+        start_index: usize,
+        max_unit: usize,
+    ) -> Vec<usize> {
+        let mut boundaries = Vec::new();
+        let mut idx = start_index;
+
+        while idx < lines.len() {
+            let next = self
+                .next_boundary(lines, line_units, idx, max_unit)
+                .min(lines.len());
+            let next = if next <= idx { idx + 1 } else { next };
+
+            boundaries.push(next);
+            idx = next;
+        }
+
+        boundaries
+    }
+}
+
+// -----------------------------------------------------------------------------
+// GreedyLineStrategy
+// -----------------------------------------------------------------------------
+//
+// clip_frag の標準の分割戦略。
+// 最大データ量を超えない範囲で、行を先頭から貪欲に詰め込む。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyLineStrategy;
+
+impl SplitStrategy for GreedyLineStrategy {
+    fn next_boundary(
+        &self,
+        lines: &[String],
+        line_units: &[usize],
+        start_index: usize,
+        max_unit: usize,
+    ) -> usize {
+        let mut used_units = 0usize;
+        let mut idx = start_index;
+
+        while idx < lines.len() {
+            // 次の行を追加すると最大データ量を超える場合は、その行は含めない。
+            if used_units + line_units[idx] > max_unit {
+                break;
+            }
+
+            used_units += line_units[idx];
+            idx += 1;
+        }
+
+        idx
+    }
+}
+
 // -----------------------------------------------------------------------------
 // build_fragment
 // -----------------------------------------------------------------------------
@@ -39,25 +137,35 @@ pub fn build_fragment(
     state: &AppState,
     start_index: usize,
 ) -> (String, usize, usize) {
-    let mut fragment = String::new();
-    let mut used_units = 0usize;
-    let mut idx = start_index;
-
-    while idx < state.lines.len() {
-        let line = &state.lines[idx];
-        let line_units = state.line_units[idx];
+    build_fragment_with(&GreedyLineStrategy, state, start_index)
+}
 
-        // 次の行を追加すると最大データ量を超える場合は、その行は含めない。
-        if used_units + line_units > state.max_unit {
-            break;
-        }
+// -----------------------------------------------------------------------------
+// build_fragment_with
+// -----------------------------------------------------------------------------
+//
+// build_fragment の分割戦略を指定できる版。
+// 境界の決定は strategy に任せ、ここでは行の連結と単位数の合計だけを行う。
+// -----------------------------------------------------------------------------
+pub fn build_fragment_with(
+    strategy: &dyn SplitStrategy,
+    state: &AppState,
+    start_index: usize,
+) -> (String, usize, usize) {
+    let start_index = start_index.min(state.lines.len());
+    let next_index = strategy
+        .next_boundary(
+            &state.lines,
+            &state.line_units,
+            start_index,
+            state.max_unit,
+        )
+        .clamp(start_index, state.lines.len());
 
-        fragment.push_str(line);
-        used_units += line_units;
-        idx += 1;
-    }
+    let fragment = state.lines[start_index..next_index].concat();
+    let used_units = state.line_units[start_index..next_index].iter().sum();
 
-    (fragment, used_units, idx)
+    (fragment, used_units, next_index)
 }
 
 // -----------------------------------------------------------------------------
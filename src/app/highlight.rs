@@ -0,0 +1,372 @@
+// ============================================================================
+// src/app/highlight.rs
+// ============================================================================
+//
+// このファイルでは、フラグメントを HTML（リッチテキスト）として clipboard に
+// 取り込むための変換（--html）を提供する。
+//
+// メールや Wiki のエディタはリッチテキストの貼り付けを受け付けるため、
+// フラグメントを <pre> で囲み、入力ファイル名の拡張子から言語を推定して
+// 簡単な構文ハイライトを付ける。
+//
+// ハイライトは字句を大まかに分けるだけの手書きの実装で、以下のみを色分けする：
+//   - コメント（行コメント・ブロックコメント）
+//   - 文字列・文字リテラル
+//   - 数値
+//   - キーワード
+//
+// 貼り付け先で CSS が消されることが多いため、色は style 属性で直接指定する。
+// フラグメントは途中の行から始まるため、ブロックコメントや複数行の文字列の
+// 途中から始まった場合は正しく色分けできないことがある。
+// ============================================================================
+
+use std::path::Path;
+
+/// コメントの色
+const COMMENT_COLOR: &str = "#6a737d";
+
+/// 文字列の色
+const STRING_COLOR: &str = "#032f62";
+
+/// 数値の色
+const NUMBER_COLOR: &str = "#005cc5";
+
+/// キーワードの色
+const KEYWORD_COLOR: &str = "#d73a49";
+
+// -----------------------------------------------------------------------------
+// Language
+// -----------------------------------------------------------------------------
+//
+// ハイライトに使う言語ごとの字句の定義。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// 言語名（<code> の class="language-..." に使う）
+    pub name: &'static str,
+
+    /// 対応する拡張子（小文字）
+    extensions: &'static [&'static str],
+
+    /// 行コメントの開始
+    line_comments: &'static [&'static str],
+
+    /// ブロックコメントの開始と終了
+    block_comment: Option<(&'static str, &'static str)>,
+
+    /// 文字列を囲む引用符
+    quotes: &'static [char],
+
+    /// キーワード（空白区切り）
+    keywords: &'static str,
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        name: "rust",
+        extensions: &["rs"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        keywords:
+            "as async await break const continue crate dyn else enum extern \
+             false fn for if impl in let loop match mod move mut pub ref \
+             return self Self static struct super trait true type unsafe use \
+             where while",
+    },
+    Language {
+        name: "c",
+        extensions: &["c", "h"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        keywords:
+            "break case char const continue default do double else enum extern \
+             float for goto if int long return short signed sizeof static \
+             struct switch typedef union unsigned void volatile while",
+    },
+    Language {
+        name: "cpp",
+        extensions: &["cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        keywords:
+            "auto bool break case catch char class const constexpr continue \
+             default delete do double else enum false float for if int long \
+             namespace new nullptr private protected public return static \
+             struct switch template this throw true try typename using virtual \
+             void while",
+    },
+    Language {
+        name: "java",
+        extensions: &["java", "kt"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        keywords:
+            "abstract boolean break case catch class continue default do else \
+             extends false final finally for fun if implements import int \
+             interface new null package private protected public return static \
+             super switch this throw throws true try val var void while",
+    },
+    Language {
+        name: "javascript",
+        extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        keywords:
+            "async await break case catch class const continue default delete \
+             do else export extends false finally for from function if import \
+             in instanceof interface let new null of return switch this throw \
+             true try type typeof undefined var void while yield",
+    },
+    Language {
+        name: "go",
+        extensions: &["go"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        keywords:
+            "break case chan const continue default defer else false for func \
+             go goto if import interface map nil package range return select \
+             struct switch true type var",
+    },
+    Language {
+        name: "python",
+        extensions: &["py", "pyi"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords:
+            "and as assert async await break class continue def del elif else \
+             except False finally for from global if import in is lambda None \
+             nonlocal not or pass raise return True try while with yield",
+    },
+    Language {
+        name: "ruby",
+        extensions: &["rb"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords:
+            "begin break case class def do else elsif end ensure false for if \
+             in module next nil require rescue return self then true unless \
+             until when while yield",
+    },
+    Language {
+        name: "bash",
+        extensions: &["sh", "bash", "zsh"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords:
+            "case do done elif else esac export fi for function if in local \
+             return then until while",
+    },
+    Language {
+        name: "sql",
+        extensions: &["sql"],
+        line_comments: &["--"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['\''],
+        keywords:
+            "and as by create delete from group insert into join left not null \
+             on or order select set table update values where",
+    },
+    Language {
+        name: "toml",
+        extensions: &["toml", "ini", "conf"],
+        line_comments: &["#", ";"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords: "true false",
+    },
+];
+
+impl Language {
+    // -------------------------------------------------------------------------
+    // Language::from_file_name
+    // -------------------------------------------------------------------------
+    //
+    // ファイル名の拡張子から言語を推定する。分からなければ None。
+    // -------------------------------------------------------------------------
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let extension =
+            Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+
+        LANGUAGES
+            .iter()
+            .find(|lang| lang.extensions.contains(&extension.as_str()))
+            .copied()
+    }
+
+    /// キーワードか（SQL は大文字・小文字を区別しない）
+    fn is_keyword(&self, word: &str) -> bool {
+        let mut keywords = self.keywords.split_ascii_whitespace();
+
+        if self.name == "sql" {
+            keywords.any(|k| k.eq_ignore_ascii_case(word))
+        } else {
+            keywords.any(|k| k == word)
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// fragment_to_html
+// -----------------------------------------------------------------------------
+//
+// フラグメントを <pre><code> の HTML にする。
+// language が分かっていれば構文ハイライトを付け、なければエスケープのみ。
+// -----------------------------------------------------------------------------
+pub fn fragment_to_html(text: &str, language: Option<Language>) -> String {
+    let body = match language {
+        Some(language) => highlight(text, &language),
+        None => escape_html(text),
+    };
+    let class = language
+        .map(|lang| format!(" class=\"language-{}\"", lang.name))
+        .unwrap_or_default();
+
+    format!(
+        "<pre style=\"font-family: monospace; white-space: pre;\"><code{}>{}</code></pre>",
+        class, body
+    )
+}
+
+// -----------------------------------------------------------------------------
+// highlight
+// -----------------------------------------------------------------------------
+//
+// text を先頭から字句に分け、色を付けた HTML にする。
+// -----------------------------------------------------------------------------
+fn highlight(text: &str, language: &Language) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // 行コメント（行末まで）
+        if language.line_comments.iter().any(|s| rest.starts_with(s)) {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            push_span(&mut out, COMMENT_COLOR, &rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        // ブロックコメント（終わりがなければ末尾まで）
+        if let Some((open, close)) = language.block_comment {
+            if rest.starts_with(open) {
+                let end = rest[open.len()..]
+                    .find(close)
+                    .map(|i| open.len() + i + close.len())
+                    .unwrap_or(rest.len());
+                push_span(&mut out, COMMENT_COLOR, &rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+        }
+
+        // 文字列（Rust の 'a のようなライフタイムは除く）
+        if language.quotes.contains(&c) && !is_lifetime(rest, language) {
+            let end = string_end(rest, c);
+            push_span(&mut out, STRING_COLOR, &rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        // 数値
+        if c.is_ascii_digit() {
+            let end = rest
+                .find(|ch: char| {
+                    !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '.')
+                })
+                .unwrap_or(rest.len());
+            push_span(&mut out, NUMBER_COLOR, &rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        // 識別子・キーワード
+        if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            if language.is_keyword(word) {
+                push_span(&mut out, KEYWORD_COLOR, word);
+            } else {
+                out.push_str(&escape_html(word));
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        out.push_str(&escape_html(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+/// Rust のライフタイム（'a）か（文字リテラル 'a' とは区別する）
+fn is_lifetime(rest: &str, language: &Language) -> bool {
+    if language.name != "rust" || !rest.starts_with('\'') {
+        return false;
+    }
+
+    let mut chars = rest.chars().skip(1);
+    match (chars.next(), chars.next()) {
+        (Some('\\'), _) => false,
+        (Some(c), Some(next)) => {
+            (c.is_alphabetic() || c == '_') && next != '\''
+        }
+        _ => false,
+    }
+}
+
+/// quote で始まる文字列の終わり（閉じる引用符の直後、なければ行末）
+fn string_end(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+
+    for (i, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            _ if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+
+    rest.len()
+}
+
+/// 色付きの <span> を追加する
+fn push_span(out: &mut String, color: &str, text: &str) {
+    out.push_str(&format!(
+        "<span style=\"color: {};\">{}</span>",
+        color,
+        escape_html(text)
+    ));
+}
+
+// -----------------------------------------------------------------------------
+// escape_html
+// -----------------------------------------------------------------------------
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
@@ -11,7 +11,8 @@
 //! 設計思想：
 //!   - App は「状態遷移の制御」に専念する。
 //!   - 純粋ロジックは fragment.rs / encoding.rs / state.rs に分離。
-//!   - I/O は tty.rs / clipboard.rs に分離。
+//!   - I/O は tty.rs / clipboard.rs に分離し、App からは Prompter /
+//!     ClipboardSink トレイト越しに使う（テストでは差し替えられる）。
 //!   - CLI パーサ（Cli）は main.rs に閉じ込める。
 //!
 //! これにより、テスト容易性・保守性・責務分離が最大化される。
@@ -19,19 +20,55 @@
 //! ============================================================================
 
 pub mod clipboard;
+pub mod command;
+pub mod config;
 pub mod encoding;
+pub mod external;
 pub mod fragment;
+pub mod highlight;
+pub mod plan;
+pub mod preview;
+pub mod prompter;
+pub mod search;
+pub mod serve;
 pub mod state;
+pub mod summary;
 pub mod tty;
+#[cfg(feature = "tui")]
+pub mod tui;
 
+pub use clipboard::{ClipboardBackend, ClipboardSink, SystemClipboard};
+pub use fragment::{GreedyLineStrategy, SplitStrategy};
+pub use plan::{FragmentPlan, FragmentSpan};
+pub use prompter::{
+    AutoAdvancePrompter, Prompter, ScriptExhausted, ScriptPrompter,
+    TtyPrompter,
+};
 pub use state::Unit;
 
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
 
-use clipboard::{clear_clipboard, set_clip_utf16};
-use fragment::{build_fragment, calc_consumed_units, format_with_underscore};
+use clipboard::{
+    set_html_verified, set_text_verified, ContentMismatch, TimedClipboard,
+    DEFAULT_HOLD_TIMEOUT,
+};
+use command::{parse_command_with, Command};
+use config::{render_template, Config};
+use external::{edit_in_editor, open_in_pager};
+use fragment::format_with_underscore;
+use highlight::{fragment_to_html, Language};
+use preview::format_preview;
+use search::search_lines;
 use state::AppState;
-use tty::read_line_from_tty;
+use summary::{format_line_range, never_sent_ranges};
+
+/// プロンプト前のプレビューに表示する先頭・末尾の行数
+const PREVIEW_LINES: usize = 3;
+
+/// 検索結果として一覧表示する最大行数
+const MAX_SEARCH_HITS: usize = 20;
 
 // ============================================================================
 // App 構造体
@@ -39,10 +76,37 @@ use tty::read_line_from_tty;
 //
 // App はアプリケーション全体の状態（AppState）を保持し、
 // main_loop → finalize_loop → exit_loop の流れを制御する。
+//
+// ユーザの入力は Prompter から、clipboard への書き込みは ClipboardSink へ
+// 行う。既定はそれぞれ TtyPrompter と SystemClipboard。
 // ============================================================================
 pub struct App {
     /// アプリケーションの状態（行データ・進捗・前回内容など）
     pub state: AppState,
+
+    /// 入力データ全体の分割計画（起動時・戦略変更時に計算する）
+    pub plan: FragmentPlan,
+
+    /// フラグメントの区切り方を決める分割戦略
+    strategy: Box<dyn SplitStrategy>,
+
+    /// ユーザの入力を受け取る Prompter
+    prompter: Box<dyn Prompter>,
+
+    /// フラグメントの取り込み先（--clear-after のため書き込み時刻を記録する）
+    clipboard: TimedClipboard,
+
+    /// 設定ファイルから読み込んだキー割り当て・プロンプト文言
+    config: Config,
+
+    /// セッション開始前の clipboard の内容（Quit で元に戻す）
+    original_clipboard: Option<String>,
+
+    /// 最後に入力を受け取った時刻（--idle-timeout の起点）
+    last_input: Instant,
+
+    /// --idle-timeout で時間切れになったか（以降の入力はすべて Quit）
+    idle_expired: bool,
 }
 
 impl App {
@@ -54,7 +118,8 @@ impl App {
     // main.rs 側で読み込んだ入力データと設定値を受け取り、
     // AppState を構築する。
     //
-    // ファイル指定時はヘッダを clipboard に入れる。
+    // ファイル指定時のヘッダは、run / run_tui の開始時に clipboard に入れる
+    // （with_clipboard で取り込み先を差し替えた後に書き込むため）。
     // ------------------------------------------------------------------------
     pub fn new(
         input_text: String,
@@ -64,7 +129,7 @@ impl App {
         input_file_name: Option<String>,
     ) -> Result<Self> {
         // AppState の構築（行分割・単位計算など）
-        let mut state = AppState::new(
+        let state = AppState::new(
             input_text,
             unit,
             max_unit,
@@ -72,19 +137,80 @@ impl App {
             input_file_name,
         );
 
-        // ファイル指定時はヘッダを clipboard に入れる
-        if state.from_file {
-            if let Some(ref name) = state.input_file_name {
-                let header = format!(
-                    "以下に、ファイル: {} を入力します。\n---\n",
-                    name
-                );
-                set_clip_utf16(header.clone())?;
-                state.prev_contents = header;
-            }
-        }
+        // 分割計画は起動時に一度だけ計算する
+        let strategy: Box<dyn SplitStrategy> = Box::new(GreedyLineStrategy);
+        let plan = FragmentPlan::new(&state, strategy.as_ref())?;
 
-        Ok(Self { state })
+        Ok(Self {
+            state,
+            plan,
+            strategy,
+            prompter: Box::new(TtyPrompter::default()),
+            clipboard: TimedClipboard::new(Box::new(
+                SystemClipboard::default(),
+            )),
+            config: Config::default(),
+            original_clipboard: None,
+            last_input: Instant::now(),
+            idle_expired: false,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // App::with_strategy
+    // ------------------------------------------------------------------------
+    //
+    // 分割戦略を差し替える。既定は GreedyLineStrategy（貪欲な行詰め込み）。
+    // 差し替えた戦略で分割計画を作り直す。戦略が不正な境界を返した場合は
+    // エラーにする。
+    //
+    //   let app = App::new(...)?.with_strategy(Box::new(MyStrategy))?;
+    // ------------------------------------------------------------------------
+    pub fn with_strategy(
+        mut self,
+        strategy: Box<dyn SplitStrategy>,
+    ) -> Result<Self> {
+        self.plan = FragmentPlan::new(&self.state, strategy.as_ref())?;
+        self.strategy = strategy;
+        Ok(self)
+    }
+
+    // ------------------------------------------------------------------------
+    // App::with_prompter
+    // ------------------------------------------------------------------------
+    //
+    // ユーザの入力を受け取る Prompter を差し替える。
+    // 1 キー入力モードは TtyPrompter::new(true) を渡す。
+    // ------------------------------------------------------------------------
+    pub fn with_prompter(mut self, prompter: Box<dyn Prompter>) -> Self {
+        self.prompter = prompter;
+        self
+    }
+
+    // ------------------------------------------------------------------------
+    // App::with_clipboard
+    // ------------------------------------------------------------------------
+    //
+    // フラグメントの取り込み先を差し替える。既定は SystemClipboard。
+    // 外部コマンドやファイルへの書き出しは ClipboardBackend::build で作る。
+    // ------------------------------------------------------------------------
+    pub fn with_clipboard(
+        mut self,
+        clipboard: Box<dyn ClipboardSink>,
+    ) -> Self {
+        self.clipboard = TimedClipboard::new(clipboard);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+    // App::with_config
+    // ------------------------------------------------------------------------
+    //
+    // キー割り当て・プロンプト文言を設定ファイルの内容に差し替える。
+    // ------------------------------------------------------------------------
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
     }
 
     // ------------------------------------------------------------------------
@@ -93,44 +219,664 @@ impl App {
     //
     // アプリケーションのメインフロー。
     // main_loop → finalize_loop（ファイル指定時）→ exit_loop の順に進む。
+    //
+    // finalize_loop / exit_loop で過去のフラグメントに移動した場合は、
+    // 移動先の次のフラグメントから main_loop をやり直す。
+    //
+    // プロセスは終了させず、セッションの終わり方（Outcome）を返す。
+    // ------------------------------------------------------------------------
+    pub fn run(&mut self) -> Result<Outcome> {
+        self.snapshot_clipboard();
+        self.copy_header()?;
+
+        loop {
+            if let Step::End(outcome) = self.main_loop()? {
+                return Ok(outcome);
+            }
+
+            if self.state.from_file {
+                match self.finalize_loop()? {
+                    Step::End(outcome) => return Ok(outcome),
+                    Step::Rewind => continue,
+                    Step::Next => {}
+                }
+            }
+
+            match self.exit_loop()? {
+                Step::End(outcome) => return Ok(outcome),
+                Step::Rewind | Step::Next => {}
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // snapshot_clipboard
+    // ------------------------------------------------------------------------
+    //
+    // 最初の書き込みの前に clipboard の内容を覚えておき、Quit で元に戻す。
+    // 取り込み先は with_clipboard で差し替えられるため、App::new ではなく
+    // run / run_tui の開始時に読む。
+    //
+    // clear_on_exit（--clear-on-exit）のときは覚えず、これまでどおり
+    // Quit でクリアする。読めなかった場合もクリアする。
+    // ------------------------------------------------------------------------
+    fn snapshot_clipboard(&mut self) {
+        self.last_input = Instant::now();
+        self.original_clipboard = if self.config.clipboard.clear_on_exit {
+            None
+        } else {
+            self.clipboard.get_text().ok().flatten()
+        };
+    }
+
+    // ------------------------------------------------------------------------
+    // copy_header
+    // ------------------------------------------------------------------------
+    //
+    // ファイル指定時は、最初にヘッダを clipboard に入れる。
+    // ------------------------------------------------------------------------
+    fn copy_header(&mut self) -> Result<()> {
+        if !self.state.from_file {
+            return Ok(());
+        }
+
+        if let Some(ref name) = self.state.input_file_name {
+            let header =
+                format!("以下に、ファイル: {} を入力します。\n---\n", name);
+            match set_text_verified(&mut self.clipboard, &header) {
+                Ok(()) => self.set_prev(header, false),
+                Err(e) => report_mismatch(e)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // read_choice
+    // ------------------------------------------------------------------------
+    //
+    // プロンプトに対するユーザの選択を Prompter から読み、キー割り当てに従って
+    // Command に変換する。
+    //
+    // 空入力（Enter のみ）は default になる。解釈できなければ None。
+    // ------------------------------------------------------------------------
+    fn read_choice(&mut self, default: Command) -> Result<Option<Command>> {
+        if !self.wait_for_input()? {
+            return Ok(Some(Command::Quit));
+        }
+
+        let starters = self.config.keys.line_starters();
+        let input = match self.prompter.read_choice(&starters) {
+            Ok(input) => input,
+            // スクリプトを使い切ったら Quit（quit のキーによらない）
+            Err(e) if e.is::<ScriptExhausted>() => {
+                return Ok(Some(Command::Quit))
+            }
+            Err(e) => return Err(e),
+        };
+        self.last_input = Instant::now();
+
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(Some(default));
+        }
+
+        Ok(parse_command_with(input, &self.config.keys))
+    }
+
+    // ------------------------------------------------------------------------
+    // read_line
+    // ------------------------------------------------------------------------
+    //
+    // 自由入力を 1 行読む（検索後のフラグメント番号など）。
+    // 選択と同じく期限を見ながら待ち、idle_timeout を過ぎたら None を返す
+    // （呼び出し側は中止として扱い、次の選択で Quit になる）。
+    // ------------------------------------------------------------------------
+    fn read_line(&mut self) -> Result<Option<String>> {
+        if !self.wait_for_input()? {
+            return Ok(None);
+        }
+
+        let input = self.prompter.read_line()?;
+        self.last_input = Instant::now();
+
+        Ok(Some(input))
+    }
+
+    // ------------------------------------------------------------------------
+    // wait_for_input
+    // ------------------------------------------------------------------------
+    //
+    // --clear-after / --idle-timeout の期限を見ながら入力を待つ。
+    // 入力が届いたら true、idle_timeout を過ぎたら false（Quit として終了する）。
+    // 一度時間切れになった後は、待たずに false を返す。
+    // どちらも指定がなければ待たずに true を返す。
+    // ------------------------------------------------------------------------
+    fn wait_for_input(&mut self) -> Result<bool> {
+        if self.idle_expired {
+            return Ok(false);
+        }
+
+        loop {
+            match self.check_deadlines()? {
+                Deadline::Cleared(message) => eprintln!("\n{}", message),
+                Deadline::Idle(message) => {
+                    eprintln!("\n{}", message);
+                    self.idle_expired = true;
+                    return Ok(false);
+                }
+                Deadline::Wait(None) => return Ok(true),
+                Deadline::Wait(Some(at)) => {
+                    let timeout = at.saturating_duration_since(Instant::now());
+                    if self.prompter.wait_for_input(timeout)? {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // check_deadlines
+    // ------------------------------------------------------------------------
+    //
+    // --clear-after / --idle-timeout の期限を確かめる（行指向・TUI で共通）。
+    //
+    //   - 取り込んでから clear_after 秒たっていれば clipboard をクリアする
+    //   - 最後の入力から idle_timeout 秒たっていれば Idle
+    //   - どちらでもなければ、次の期限（なければ None）まで待つ
+    // ------------------------------------------------------------------------
+    fn check_deadlines(&mut self) -> Result<Deadline> {
+        let settings = &self.config.clipboard;
+        let clear_after = settings.clear_after.map(Duration::from_secs);
+        let idle_timeout = settings.idle_timeout.map(Duration::from_secs);
+
+        let now = Instant::now();
+        let clear_at = clear_after
+            .zip(self.clipboard.written_at())
+            .map(|(after, written)| written + after);
+        let idle_at = idle_timeout.map(|idle| self.last_input + idle);
+
+        if let Some(after) =
+            clear_after.filter(|_| clear_at.is_some_and(|at| at <= now))
+        {
+            self.clipboard.clear()?;
+            return Ok(Deadline::Cleared(format!(
+                "{} 秒たったため clipboard をクリアしました。",
+                after.as_secs()
+            )));
+        }
+
+        if let Some(idle) =
+            idle_timeout.filter(|_| idle_at.is_some_and(|at| at <= now))
+        {
+            return Ok(Deadline::Idle(format!(
+                "{} 秒間操作がなかったため終了します。",
+                idle.as_secs()
+            )));
+        }
+
+        Ok(Deadline::Wait(clear_at.into_iter().chain(idle_at).min()))
+    }
+
+    // ------------------------------------------------------------------------
+    // next_fragment / cursor
+    // ------------------------------------------------------------------------
+    //
+    // next_fragment: 次に取り込む（プロンプトで提示する）フラグメントの index。
+    //                全行処理済みなら plan.len()。
+    // cursor:        最後に取り込んだ位置のフラグメントの index。
+    //                まだ何も取り込んでいなければ None。
+    //
+    // ナビゲーションコマンドの移動量は cursor を基準にする。
+    // ------------------------------------------------------------------------
+    fn next_fragment(&self) -> usize {
+        self.plan
+            .fragment_at_line(self.state.curr_index)
+            .unwrap_or(self.plan.len())
+    }
+
+    fn cursor(&self) -> Option<usize> {
+        self.next_fragment().checked_sub(1)
+    }
+
+    // ------------------------------------------------------------------------
+    // copy_fragment
+    // ------------------------------------------------------------------------
+    //
+    // index 番目のフラグメントを clipboard に取り込み、
+    // prev_contents・curr_index・history を更新する。
+    // ------------------------------------------------------------------------
+    fn copy_fragment(&mut self, index: usize) -> Result<()> {
+        let span = self.plan.fragments()[index];
+        let fragment = self.state.lines[span.start..span.end].concat();
+
+        self.write_fragment(&fragment)?;
+        self.set_prev(fragment, true);
+        self.state.curr_index = span.end;
+        self.state.history.push(span.start..span.end);
+
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // write_fragment / copy_prev
+    // ------------------------------------------------------------------------
+    //
+    // write_fragment: フラグメントを clipboard に書き込む。--html なら、
+    //                 入力ファイル名から推定した言語でハイライトした HTML と、
+    //                 代替テキスト（フラグメントそのもの）の組で書き込む。
+    // copy_prev:      前回の内容を同じ形式で取り込み直す（P(rev)）。
+    //                 ヘッダ・フッタは --html でもテキストのまま。
+    // set_prev:       前回の内容と、それがフラグメントかどうかを記録する。
     // ------------------------------------------------------------------------
-    pub fn run(&mut self) -> Result<()> {
-        self.main_loop()?;
+    fn write_fragment(&mut self, text: &str) -> Result<()> {
+        if !self.config.clipboard.html {
+            return set_text_verified(&mut self.clipboard, text);
+        }
+
+        let language = self
+            .state
+            .input_file_name
+            .as_deref()
+            .and_then(Language::from_file_name);
+        let html = fragment_to_html(text, language);
 
-        if self.state.from_file {
-            self.finalize_loop()?;
+        set_html_verified(&mut self.clipboard, &html, text)
+    }
+
+    fn copy_prev(&mut self) -> Result<()> {
+        let prev = self.state.prev_contents.clone();
+
+        if self.state.prev_is_fragment {
+            self.write_fragment(&prev)
+        } else {
+            set_text_verified(&mut self.clipboard, &prev)
         }
+    }
 
-        self.exit_loop()?;
+    fn set_prev(&mut self, contents: String, is_fragment: bool) {
+        self.state.prev_contents = contents;
+        self.state.prev_is_fragment = is_fragment;
+    }
+
+    // ------------------------------------------------------------------------
+    // edit_and_copy
+    // ------------------------------------------------------------------------
+    //
+    // index 番目のフラグメントを $EDITOR で編集し、編集後のテキストを
+    // clipboard に取り込む。取り込み後の状態更新は copy_fragment と同じ。
+    //
+    // 編集後のテキストが最大データ量を超える場合は警告だけ出して取り込む。
+    // エディタの起動に失敗した場合や、編集後が空の場合は取り込まない。
+    // ------------------------------------------------------------------------
+    fn edit_and_copy(&mut self, index: usize) -> Result<()> {
+        let span = self.plan.fragments()[index];
+        let fragment = self.state.lines[span.start..span.end].concat();
+
+        let extension = self
+            .state
+            .input_file_name
+            .as_deref()
+            .and_then(|name| std::path::Path::new(name).extension())
+            .and_then(|ext| ext.to_str());
+
+        let edited = match edit_in_editor(&fragment, extension) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("エディタでの編集に失敗しました: {:#}", e);
+                return Ok(());
+            }
+        };
+
+        if edited.trim().is_empty() {
+            eprintln!("編集後のテキストが空のため、取り込みを中止しました。");
+            return Ok(());
+        }
+
+        let edited_units = self.state.unit.count(&edited);
+        if edited_units > self.state.max_unit {
+            eprintln!(
+                "注意: 編集後のテキスト（{} [{}]）が最大データ量 {} を超えています。",
+                format_with_underscore(edited_units),
+                unit_label(self.state.unit),
+                format_with_underscore(self.state.max_unit)
+            );
+        }
+
+        self.write_fragment(&edited)?;
+        self.set_prev(edited, true);
+        self.state.curr_index = span.end;
+        self.state.history.push(span.start..span.end);
 
         Ok(())
     }
 
+    // ------------------------------------------------------------------------
+    // navigate
+    // ------------------------------------------------------------------------
+    //
+    // Back / Forward / Goto / First / Last を処理する。
+    // 移動先のフラグメントを clipboard に取り込み、次の提示は移動先の次になる。
+    //
+    // 戻り値：
+    //   true  — 移動して取り込んだ
+    //   false — ナビゲーションコマンドではない、または移動先が範囲外
+    // ------------------------------------------------------------------------
+    fn navigate(&mut self, command: Command) -> Result<bool> {
+        let len = self.plan.len();
+        let cursor = self.cursor();
+
+        let target = match command {
+            Command::Back(n) => cursor.and_then(|c| c.checked_sub(n)),
+            Command::Forward(n) => match cursor {
+                Some(c) => c.checked_add(n),
+                None => n.checked_sub(1),
+            },
+            Command::Goto(k) => k.checked_sub(1),
+            Command::First => Some(0),
+            Command::Last => len.checked_sub(1),
+            _ => return Ok(false),
+        };
+
+        match target {
+            Some(index) if index < len => {
+                if let Err(e) = self.copy_fragment(index) {
+                    report_mismatch(e)?;
+                    return Ok(false);
+                }
+                eprintln!("fragment {}/{} を取り込みました。", index + 1, len);
+                Ok(true)
+            }
+            _ => {
+                eprintln!("移動先のフラグメントがありません（1〜{}）。", len);
+                Ok(false)
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // search_and_jump
+    // ------------------------------------------------------------------------
+    //
+    // 入力データを pattern（正規表現）で検索し、一致した行をフラグメントごとに
+    // 一覧表示する。続けてフラグメント番号を入力すると、そこへ移動して
+    // clipboard に取り込む（Enter のみで中止）。
+    //
+    // 戻り値：
+    //   true  — 移動して取り込んだ
+    //   false — 一致なし・中止・パターン不正など
+    // ------------------------------------------------------------------------
+    fn search_and_jump(&mut self, pattern: &str) -> Result<bool> {
+        let hits = match search_lines(&self.state.lines, &self.plan, pattern) {
+            Ok(hits) => hits,
+            Err(e) => {
+                eprintln!("検索パターンが不正です: {:#}", e);
+                return Ok(false);
+            }
+        };
+
+        if hits.is_empty() {
+            eprintln!("一致する行はありません: /{}", pattern);
+            return Ok(false);
+        }
+
+        // フラグメントごとにまとめて表示する（行数が多い場合は打ち切る）
+        let mut last_fragment = None;
+        for hit in hits.iter().take(MAX_SEARCH_HITS) {
+            if last_fragment != Some(hit.fragment) {
+                let span = self.plan.fragments()[hit.fragment];
+                eprintln!(
+                    "fragment {}/{} ({}):",
+                    hit.fragment + 1,
+                    self.plan.len(),
+                    format_line_range(&(span.start..span.end))
+                );
+                last_fragment = Some(hit.fragment);
+            }
+            eprint!(
+                "{}",
+                format_preview(
+                    &self.state.lines,
+                    &(hit.line..hit.line + 1),
+                    1,
+                    0
+                )
+            );
+        }
+        if hits.len() > MAX_SEARCH_HITS {
+            eprintln!("... ほか {} 件", hits.len() - MAX_SEARCH_HITS);
+        }
+
+        eprint!("移動するフラグメント番号（Enter で中止）: ");
+        let Some(input) = self.read_line()? else {
+            return Ok(false);
+        };
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(false);
+        }
+
+        match input.parse::<usize>() {
+            Ok(k) => self.navigate(Command::Goto(k)),
+            Err(_) => {
+                eprintln!("フラグメント番号を数字で入力してください。");
+                Ok(false)
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // resize
+    // ------------------------------------------------------------------------
+    //
+    // セッション途中で最大データ量・単位を変更する。
+    // 取り込み済みの位置（curr_index）は保ったまま、そこから先だけを
+    // 新しい設定で分割し直す。
+    // ------------------------------------------------------------------------
+    fn resize(&mut self, unit: Unit, max_unit: usize) -> Result<()> {
+        self.state.set_unit(unit, max_unit);
+        self.plan.replan(
+            &self.state,
+            self.strategy.as_ref(),
+            self.state.curr_index,
+        )?;
+
+        eprintln!(
+            "最大データ量を {} [{}] に変更しました（全 {} フラグメント）。",
+            format_with_underscore(max_unit),
+            unit_label(unit),
+            self.plan.len()
+        );
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // adjust_end
+    // ------------------------------------------------------------------------
+    //
+    // 提示中のフラグメント（frag_index）の終端を +/-/]/[ で動かす。
+    // 貪欲な詰め込みが自然な区切りの 1 行手前で終わってしまう場合などに使う。
+    //
+    // 終端を動かした後ろのフラグメントは分割し直す。
+    // 最大データ量を超えるまで広げることも許し、プロンプトで警告する。
+    // ------------------------------------------------------------------------
+    fn adjust_end(
+        &mut self,
+        frag_index: usize,
+        command: Command,
+    ) -> Result<()> {
+        let span = self.plan.fragments()[frag_index];
+        let lines = &self.state.lines;
+        let is_blank = |i: usize| lines[i].trim().is_empty();
+
+        let new_end = match command {
+            Command::Grow(n) => Some(span.end.saturating_add(n)),
+            Command::Shrink(n) => Some(span.end.saturating_sub(n)),
+            // 次の空行（それを含む）まで広げる
+            Command::NextBlank => {
+                (span.end..lines.len()).find(|&i| is_blank(i)).map(|i| i + 1)
+            }
+            // 末尾の行より前にある空行（それを含む）まで縮める
+            Command::PrevBlank => (span.start..span.end.saturating_sub(1))
+                .rev()
+                .find(|&i| is_blank(i))
+                .map(|i| i + 1),
+            _ => None,
+        };
+
+        let Some(new_end) = new_end else {
+            eprintln!("移動先の空行が見つかりません。");
+            return Ok(());
+        };
+
+        if new_end <= span.start || new_end > lines.len() {
+            eprintln!("これ以上フラグメントの範囲を変更できません。");
+            return Ok(());
+        }
+
+        self.plan.set_end(
+            &self.state,
+            self.strategy.as_ref(),
+            frag_index,
+            new_end,
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    // quit
+    // ------------------------------------------------------------------------
+    //
+    // Quit 共通の終了処理。
+    // clipboard をセッション開始前の内容に戻し（なければクリアし）、
+    // セッションのまとめを表示する。戻せなかった場合は知らせてクリアする。
+    // プロセスの終了は呼び出し側（main.rs）に任せる。
+    //
+    // X11 / Wayland では clipboard の内容は書き込んだプロセスが持つため、
+    // 戻した内容も clip_frag の終了とともに消えてしまう。Keep と同じく
+    // persist で保持プロセスに引き継ぐ（他の OS・取り込み先では何もしない）。
+    // ------------------------------------------------------------------------
+    fn quit(&mut self) -> Result<Outcome> {
+        let result = match self.original_clipboard.take() {
+            Some(original) => self.restore(&original).or_else(|e| {
+                // 戻せなかった場合は、最後のフラグメントを残さないよう
+                // クリアする
+                eprintln!(
+                    "エラー: 起動前の clipboard の内容に戻せませんでした（{:#}）。clipboard をクリアします。",
+                    e
+                );
+                self.clipboard.clear()
+            }),
+            None => self.clipboard.clear(),
+        };
+
+        // clipboard を戻せなくても、まとめは表示する
+        self.print_summary();
+        result.map(|()| Outcome::Quit)
+    }
+
+    /// original を clipboard に戻し、保持プロセスに引き継ぐ
+    fn restore(&mut self, original: &str) -> Result<()> {
+        set_text_verified(&mut self.clipboard, original)?;
+        self.clipboard.persist(original, self.hold_timeout())
+    }
+
+    // ------------------------------------------------------------------------
+    // keep
+    // ------------------------------------------------------------------------
+    //
+    // Keep の終了処理。clipboard はクリアせず、最後に取り込んだ内容が
+    // 終了後も貼り付けられるようにする（X11 / Wayland では保持プロセスを
+    // 起動する）。保持する秒数は設定ファイル / --hold-timeout で決める。
+    //
+    // --clear-after で clipboard をクリアした後（期限が過ぎていればここで
+    // クリアする）は、クリアを取り消さないよう内容を残さない。
+    // ------------------------------------------------------------------------
+    fn keep(&mut self) -> Result<Outcome> {
+        let timeout = self.hold_timeout();
+
+        if let Deadline::Cleared(message) = self.check_deadlines()? {
+            eprintln!("{}", message);
+        }
+        let cleared = self.config.clipboard.clear_after.is_some()
+            && self.clipboard.written_at().is_none();
+
+        if cleared {
+            eprintln!(
+                "clipboard はクリア済みのため、内容を残さずに終了します。"
+            );
+        } else if !self.state.prev_contents.is_empty() {
+            self.clipboard.persist(&self.state.prev_contents, timeout)?;
+            eprintln!(
+                "clipboard の内容を残して終了します（最大 {} 秒）。",
+                timeout.as_secs()
+            );
+        }
+
+        self.print_summary();
+        Ok(Outcome::Keep)
+    }
+
+    /// 保持プロセスが clipboard を持ち続ける時間（設定ファイル / --hold-timeout）
+    fn hold_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.config.clipboard.hold_timeout.unwrap_or(DEFAULT_HOLD_TIMEOUT),
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    // print_summary
+    // ------------------------------------------------------------------------
+    //
+    // セッションのまとめを標準エラー出力に表示する。
+    // 一度も取り込まなかった行範囲（スキップ・移動で飛ばした行、途中で
+    // 終了して残った行）があれば列挙する。
+    // ------------------------------------------------------------------------
+    fn print_summary(&self) {
+        let never_sent =
+            never_sent_ranges(self.state.lines.len(), &self.state.history);
+
+        if never_sent.is_empty() {
+            return;
+        }
+
+        let list: Vec<String> =
+            never_sent.iter().map(format_line_range).collect();
+        eprintln!("未送信: {}", list.join(", "));
+    }
+
     // ------------------------------------------------------------------------
     // main_loop
     // ------------------------------------------------------------------------
     //
     // 分割処理の本体。
-    // curr_index から始めて、最大データ量を超えない範囲で行を詰め込み、
+    // curr_index を含むフラグメントを分割計画（FragmentPlan）から取り出し、
     // ユーザに Yes/Prev/Quit を問い合わせる。
     //
-    // fragment.rs の build_fragment が純粋ロジックとして分割を担当する。
+    // フラグメントの行範囲・単位数・累積単位数は計画済みなので、
+    // ここでの進捗計算は O(1) で済む。
+    //
+    // 全行を処理し終えたら Step::Next、Quit なら Step::End を返す。
     // ------------------------------------------------------------------------
-    fn main_loop(&mut self) -> Result<()> {
+    fn main_loop(&mut self) -> Result<Step> {
         loop {
             // 全行処理済みなら終了
             if self.state.curr_index >= self.state.lines.len() {
                 break;
             }
 
-            // フラグメント構築（純粋ロジック）
-            let (fragment, fragment_units, next_index) =
-                build_fragment(&self.state, self.state.curr_index);
+            // 現在のフラグメントを分割計画から取り出す
+            let Some(frag_index) =
+                self.plan.fragment_at_line(self.state.curr_index)
+            else {
+                break;
+            };
+            let span = self.plan.fragments()[frag_index];
+            let fragment_units = span.units;
 
-            // 進捗計算（純粋ロジック）
-            let consumed_before =
-                calc_consumed_units(&self.state, self.state.curr_index);
-            let consumed_after = consumed_before + fragment_units;
+            // 進捗計算（計画済みの累積値を使う）
+            let consumed_after = span.consumed_after();
 
             let percent_fragment = if self.state.total_units == 0 {
                 0.0
@@ -146,53 +892,124 @@ impl App {
                     / (self.state.total_units as f64)
             };
 
-            let unit_label = match self.state.unit {
-                Unit::Chars => "chars",
-                Unit::Bytes => "bytes",
-            };
+            let unit_label = unit_label(self.state.unit);
 
-            let frag_str = format_with_underscore(fragment_units);
-            let total_str = format_with_underscore(self.state.total_units);
-            let cumu_str = format_with_underscore(consumed_after);
+            if fragment_units > self.state.max_unit {
+                eprintln!(
+                    "注意: このフラグメントは最大データ量 {} [{}] を超えています。",
+                    format_with_underscore(self.state.max_unit),
+                    unit_label
+                );
+            }
 
-            // プロンプト表示
+            // プレビュー（行範囲の先頭・末尾の数行）
             eprint!(
-                "+{} [{}] ({:.1} %), {} / {} ({:.1} %): Y(es)/P(rev)/Q(uit) [y]: ",
-                frag_str, unit_label, percent_fragment,
-                cumu_str, total_str, percent_cumulative
+                "{}",
+                format_preview(
+                    &self.state.lines,
+                    &(span.start..span.end),
+                    PREVIEW_LINES,
+                    PREVIEW_LINES,
+                )
             );
 
-            // TTY 入力（tty.rs）
-            let input = read_line_from_tty()?.trim().to_string();
-            let decision = if input.is_empty() { "y" } else { &input };
+            // プロンプト表示（文言は設定ファイルで変更できる）
+            let vars = [
+                ("index", (frag_index + 1).to_string()),
+                ("count", self.plan.len().to_string()),
+                ("range", format_line_range(&(span.start..span.end))),
+                ("units", format_with_underscore(fragment_units)),
+                ("unit", unit_label.to_string()),
+                ("percent", format!("{:.1}", percent_fragment)),
+                ("cumulative", format_with_underscore(consumed_after)),
+                ("total", format_with_underscore(self.state.total_units)),
+                ("cumulative_percent", format!("{:.1}", percent_cumulative)),
+            ];
+            eprint!("{}", render_template(&self.config.prompts.main, &vars));
 
-            match decision.to_lowercase().as_str() {
-                "y" | "yes" => {
+            // TTY 入力（tty.rs）
+            match self.read_choice(Command::Yes)? {
+                Some(Command::Yes) => {
                     // fragment を clipboard に取り込む
-                    set_clip_utf16(fragment.clone())?;
-                    self.state.prev_contents = fragment;
-                    self.state.curr_index = next_index;
+                    // （一致しなければ知らせて、同じフラグメントを再提示する）
+                    if let Err(e) = self.copy_fragment(frag_index) {
+                        report_mismatch(e)?;
+                        continue;
+                    }
 
                     if self.state.curr_index >= self.state.lines.len() {
                         break;
                     }
                 }
-                "p" | "prev" => {
+                Some(Command::Prev) => {
                     // 前回内容を clipboard に戻す
-                    set_clip_utf16(self.state.prev_contents.clone())?;
+                    self.copy_prev().or_else(report_mismatch)?;
                 }
-                "q" | "quit" => {
+                Some(Command::Quit) => {
                     // 終了
-                    clear_clipboard()?;
-                    std::process::exit(0);
+                    return Ok(Step::End(self.quit()?));
+                }
+                Some(Command::Skip) => {
+                    // clipboard には取り込まずに次へ進む
+                    self.state.skipped.push(span.start..span.end);
+                    self.state.curr_index = span.end;
+
+                    if self.state.curr_index >= self.state.lines.len() {
+                        break;
+                    }
+                }
+                Some(Command::Resize { max_unit, unit }) => {
+                    // 最大データ量・単位を変更し、現在位置から分割し直す
+                    self.resize(
+                        unit.unwrap_or(self.state.unit),
+                        max_unit.unwrap_or(self.state.max_unit),
+                    )?;
+                }
+                Some(
+                    adjust @ (Command::Grow(_)
+                    | Command::Shrink(_)
+                    | Command::NextBlank
+                    | Command::PrevBlank),
+                ) => {
+                    // 提示中のフラグメントの終端を動かす（取り込みはしない）
+                    self.adjust_end(frag_index, adjust)?;
+                }
+                Some(Command::View) => {
+                    // 提示中のフラグメント全体をページャで確認する
+                    let text = self.state.lines[span.start..span.end].concat();
+                    if let Err(e) = open_in_pager(&text) {
+                        eprintln!("ページャを起動できませんでした: {:#}", e);
+                    }
+                }
+                Some(Command::Edit) => {
+                    // エディタで編集した内容を取り込む
+                    if let Err(e) = self.edit_and_copy(frag_index) {
+                        report_mismatch(e)?;
+                        continue;
+                    }
+
+                    if self.state.curr_index >= self.state.lines.len() {
+                        break;
+                    }
+                }
+                Some(Command::Search(pattern)) => {
+                    // 検索して、選んだフラグメントへ移動して取り込む
+                    self.search_and_jump(&pattern)?;
+                }
+                Some(Command::Help) => {
+                    eprint!("{}", self.config.keys.help_text());
+                }
+                Some(nav) if nav.is_navigation() => {
+                    // 過去・未来のフラグメントへ移動して取り込む
+                    self.navigate(nav)?;
                 }
                 _ => {
-                    eprintln!("無効な入力です。Y(es)/P(rev)/Q(uit) を入力してください。");
+                    eprintln!("{}", self.config.prompts.invalid);
                 }
             }
         }
 
-        Ok(())
+        Ok(Step::Next)
     }
 
     // ------------------------------------------------------------------------
@@ -202,42 +1019,64 @@ impl App {
     // ファイル指定時のみ実行される「クローズ処理」。
     // 最後に「以上が、ファイル: <名前> の内容である。」を貼るかどうか確認する。
     // ------------------------------------------------------------------------
-    fn finalize_loop(&mut self) -> Result<()> {
+    fn finalize_loop(&mut self) -> Result<Step> {
         loop {
-            eprint!("+footer prompt: Y(es)/P(rev)/Q(uit) [y]: ");
-
-            let input = read_line_from_tty()?.trim().to_string();
-            let decision = if input.is_empty() { "y" } else { &input };
-
-            match decision.to_lowercase().as_str() {
-                "y" | "yes" => {
-                    let footer = if let Some(ref name) =
-                        self.state.input_file_name
-                    {
-                        format!("以上が、ファイル: {} の内容である。\n", name)
-                    } else {
-                        "以上が、入力データの内容である。\n".to_string()
-                    };
+            eprint!("{}", self.config.prompts.footer);
 
-                    set_clip_utf16(footer.clone())?;
-                    self.state.prev_contents = footer;
+            match self.read_choice(Command::Yes)? {
+                Some(Command::Yes) => {
+                    let footer = self.footer_text();
 
-                    break;
+                    match set_text_verified(&mut self.clipboard, &footer) {
+                        Ok(()) => {
+                            self.set_prev(footer, false);
+                            return Ok(Step::Next);
+                        }
+                        Err(e) => report_mismatch(e)?,
+                    }
+                }
+                Some(Command::Prev) => {
+                    self.copy_prev().or_else(report_mismatch)?;
                 }
-                "p" | "prev" => {
-                    set_clip_utf16(self.state.prev_contents.clone())?;
+                Some(Command::Quit) => {
+                    return Ok(Step::End(self.quit()?));
                 }
-                "q" | "quit" => {
-                    clear_clipboard()?;
-                    std::process::exit(0);
+                Some(Command::Skip) => {
+                    // フッタを取り込まずに終了処理へ進む
+                    return Ok(Step::Next);
+                }
+                Some(Command::Search(pattern)) => {
+                    if self.search_and_jump(&pattern)? {
+                        return Ok(Step::Rewind);
+                    }
+                }
+                Some(Command::Help) => {
+                    eprint!("{}", self.config.keys.help_text());
+                }
+                Some(nav) if nav.is_navigation() => {
+                    if self.navigate(nav)? {
+                        return Ok(Step::Rewind);
+                    }
                 }
                 _ => {
-                    eprintln!("無効な入力です。Y(es)/P(rev)/Q(uit) を入力してください。");
+                    eprintln!("{}", self.config.prompts.invalid);
                 }
             }
         }
+    }
 
-        Ok(())
+    // ------------------------------------------------------------------------
+    // footer_text
+    // ------------------------------------------------------------------------
+    //
+    // ファイナライズ処理で clipboard に取り込むフッタ。
+    // ------------------------------------------------------------------------
+    fn footer_text(&self) -> String {
+        if let Some(ref name) = self.state.input_file_name {
+            format!("以上が、ファイル: {} の内容である。\n", name)
+        } else {
+            "以上が、入力データの内容である。\n".to_string()
+        }
     }
 
     // ------------------------------------------------------------------------
@@ -245,29 +1084,114 @@ impl App {
     // ------------------------------------------------------------------------
     //
     // 最終終了処理。
-    // P(rev)/Q(uit) のみ。
+    // P(rev)/K(eep)/Q(uit) と、過去のフラグメントへの移動のみ。
     // ------------------------------------------------------------------------
-    fn exit_loop(&mut self) -> Result<()> {
+    fn exit_loop(&mut self) -> Result<Step> {
         loop {
-            eprint!("P(rev)/Q(uit) [q]: ");
-
-            let input = read_line_from_tty()?.trim().to_string();
-            let decision = if input.is_empty() { "q" } else { &input };
+            eprint!("{}", self.config.prompts.exit);
 
-            match decision.to_lowercase().as_str() {
-                "p" | "prev" => {
-                    set_clip_utf16(self.state.prev_contents.clone())?;
+            match self.read_choice(Command::Quit)? {
+                Some(Command::Prev) => {
+                    self.copy_prev().or_else(report_mismatch)?;
                 }
-                "q" | "quit" => {
-                    clear_clipboard()?;
-                    std::process::exit(0);
+                Some(Command::Quit) => {
+                    return Ok(Step::End(self.quit()?));
+                }
+                Some(Command::Keep) => {
+                    return Ok(Step::End(self.keep()?));
+                }
+                Some(Command::Search(pattern)) => {
+                    if self.search_and_jump(&pattern)? {
+                        return Ok(Step::Rewind);
+                    }
+                }
+                Some(Command::Help) => {
+                    eprint!("{}", self.config.keys.help_text());
+                }
+                Some(nav) if nav.is_navigation() => {
+                    if self.navigate(nav)? {
+                        return Ok(Step::Rewind);
+                    }
                 }
                 _ => {
-                    eprintln!(
-                        "無効な入力です。P(rev)/Q(uit) を入力してください。"
-                    );
+                    eprintln!("{}", self.config.prompts.invalid_exit);
                 }
             }
         }
     }
 }
+
+// ============================================================================
+// report_mismatch
+// ============================================================================
+//
+// clipboard の内容が一致しなかった（ContentMismatch）ことをプロンプトの前に
+// 知らせる。呼び出し側は先へ進まずにプロンプトを出し直す。
+// それ以外のエラーはそのまま返す。
+// ============================================================================
+fn report_mismatch(e: anyhow::Error) -> Result<()> {
+    if e.is::<ContentMismatch>() {
+        eprintln!("エラー: {}。もう一度取り込んでください。", e);
+        Ok(())
+    } else {
+        Err(e)
+    }
+}
+
+// ============================================================================
+// unit_label
+// ============================================================================
+//
+// プロンプト表示用の単位名。
+// ============================================================================
+fn unit_label(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Chars => "chars",
+        Unit::Bytes => "bytes",
+    }
+}
+
+// ============================================================================
+// Outcome
+// ============================================================================
+//
+// セッションの終わり方。App::run / App::run_tui の戻り値。
+//   - Quit: Quit コマンドで終了した（clipboard は元の内容に戻すかクリア済み）
+//   - Keep: Keep コマンドで終了した（clipboard の内容は残したまま）
+// ============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Quit,
+    Keep,
+}
+
+// ============================================================================
+// Deadline
+// ============================================================================
+//
+// check_deadlines の結果。
+//   - Cleared: clear_after が過ぎたので clipboard をクリアした（知らせる文言）
+//   - Idle:    idle_timeout が過ぎたので終了する（知らせる文言）
+//   - Wait:    この時刻まで入力を待つ（None なら期限なし）
+// ============================================================================
+enum Deadline {
+    Cleared(String),
+    Idle(String),
+    Wait(Option<Instant>),
+}
+
+// ============================================================================
+// Step
+// ============================================================================
+//
+// main_loop / finalize_loop / exit_loop の終わり方。
+//   - Next:   次の処理に進む
+//   - Rewind: 過去のフラグメントに移動したので main_loop からやり直す
+//   - End:    セッションを終える
+// ============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Next,
+    Rewind,
+    End(Outcome),
+}
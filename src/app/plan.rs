@@ -0,0 +1,263 @@
+// ============================================================================
+// src/app/plan.rs
+// ============================================================================
+//
+// このファイルでは、入力データ全体の「分割計画」を表す FragmentPlan を定義する。
+//
+// FragmentPlan は起動時に計算し、すべてのフラグメントについて
+//
+//   - 行範囲（start..end）
+//   - フラグメントの単位数
+//   - そのフラグメントより前に消費した単位数（累積）
+//
+// を保持する。App はこれを使って
+//
+//   - 「fragment 3/12」のような表示
+//   - 任意のフラグメントへの移動（ランダムアクセス）
+//   - O(1) の進捗計算
+//
+// を行う。セッション途中で最大データ量や単位、フラグメントの終端を
+// 変えた場合は、変えた位置から後ろだけを分割し直す（replan / set_end）。
+// ============================================================================
+
+use anyhow::{bail, Result};
+
+use super::fragment::SplitStrategy;
+use super::state::AppState;
+
+// -----------------------------------------------------------------------------
+// FragmentSpan
+// -----------------------------------------------------------------------------
+//
+// 1 つのフラグメントの行範囲と単位数。
+// 行範囲は半開区間 [start, end) で表す。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentSpan {
+    /// 開始行 index
+    pub start: usize,
+
+    /// 終端行 index（次のフラグメントの開始行）
+    pub end: usize,
+
+    /// このフラグメントの単位数（chars または bytes）
+    pub units: usize,
+
+    /// このフラグメントより前に消費した単位数の累計
+    pub consumed_before: usize,
+}
+
+impl FragmentSpan {
+    /// このフラグメントまでを取り込んだ時点の累積単位数
+    pub fn consumed_after(&self) -> usize {
+        self.consumed_before + self.units
+    }
+}
+
+// -----------------------------------------------------------------------------
+// FragmentPlan
+// -----------------------------------------------------------------------------
+//
+// 入力データ全体をフラグメントに分割した結果。
+// フラグメントは先頭から順に、隙間なく並んでいる。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Default)]
+pub struct FragmentPlan {
+    fragments: Vec<FragmentSpan>,
+}
+
+impl FragmentPlan {
+    // -------------------------------------------------------------------------
+    // FragmentPlan::new
+    // -------------------------------------------------------------------------
+    //
+    // AppState の行データと単位数から、strategy に従って分割計画を作る。
+    //
+    // エラー：strategy が不正な境界を返した場合（replan を参照）。
+    // -------------------------------------------------------------------------
+    pub fn new(
+        state: &AppState,
+        strategy: &dyn SplitStrategy,
+    ) -> Result<Self> {
+        let mut plan = Self::default();
+        plan.replan(state, strategy, 0)?;
+        Ok(plan)
+    }
+
+    // -------------------------------------------------------------------------
+    // FragmentPlan::replan
+    // -------------------------------------------------------------------------
+    //
+    // from_line より前のフラグメントはそのまま残し、from_line 以降だけを
+    // strategy に従って分割し直す。セッション途中で最大データ量や単位を
+    // 変更したときに使う。
+    //
+    // 単位が変わっている可能性があるため、残したフラグメントの単位数と
+    // 累積値も state.line_units から計算し直す。
+    //
+    // エラー：strategy の境界が plan_boundaries の約束を守っていない場合。
+    // このときは分割計画を変更しない。
+    // -------------------------------------------------------------------------
+    pub fn replan(
+        &mut self,
+        state: &AppState,
+        strategy: &dyn SplitStrategy,
+        from_line: usize,
+    ) -> Result<()> {
+        let from_line = from_line.min(state.lines.len());
+        let boundaries = strategy.plan_boundaries(
+            &state.lines,
+            &state.line_units,
+            from_line,
+            state.max_unit,
+        );
+        check_boundaries(&boundaries, from_line, state.lines.len())?;
+
+        // from_line 以降に始まるフラグメントを捨て、
+        // from_line をまたぐフラグメントは from_line で打ち切る
+        self.fragments.retain(|f| f.start < from_line);
+        if let Some(last) = self.fragments.last_mut() {
+            last.end = last.end.min(from_line);
+        }
+
+        let kept: Vec<usize> = self.fragments.iter().map(|f| f.end).collect();
+
+        self.fragments.clear();
+        let mut start = 0usize;
+        let mut consumed = 0usize;
+
+        for end in kept.into_iter().chain(boundaries) {
+            let units = state.line_units[start..end].iter().sum();
+            self.fragments.push(FragmentSpan {
+                start,
+                end,
+                units,
+                consumed_before: consumed,
+            });
+
+            consumed += units;
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------
+    // FragmentPlan::set_end
+    // -------------------------------------------------------------------------
+    //
+    // index 番目のフラグメントの終端を new_end に変更し、
+    // それより後ろを strategy に従って分割し直す。
+    // 提示中のフラグメントを 1 行ずつ広げたり縮めたりするときに使う。
+    //
+    // new_end はフラグメントの開始行より後ろ、かつ行数以下に丸める。
+    //
+    // エラー：strategy が不正な境界を返した場合（分割計画は変更しない）。
+    // -------------------------------------------------------------------------
+    pub fn set_end(
+        &mut self,
+        state: &AppState,
+        strategy: &dyn SplitStrategy,
+        index: usize,
+        new_end: usize,
+    ) -> Result<()> {
+        let Some(span) = self.fragments.get(index) else {
+            return Ok(());
+        };
+        let new_end = new_end.clamp(span.start + 1, state.lines.len());
+
+        let mut plan = self.clone();
+        plan.fragments.truncate(index + 1);
+        plan.fragments[index].end = new_end;
+        plan.replan(state, strategy, new_end)?;
+
+        *self = plan;
+        Ok(())
+    }
+
+    /// フラグメント数
+    pub fn len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// フラグメントが 1 つもない（入力が空）かどうか
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+
+    /// index 番目（0 始まり）のフラグメント
+    pub fn get(&self, index: usize) -> Option<&FragmentSpan> {
+        self.fragments.get(index)
+    }
+
+    /// すべてのフラグメント
+    pub fn fragments(&self) -> &[FragmentSpan] {
+        &self.fragments
+    }
+
+    // -------------------------------------------------------------------------
+    // fragment_at_line
+    // -------------------------------------------------------------------------
+    //
+    // 行 index line を含むフラグメントの index を返す。
+    // フラグメントは start 順に並んでいるので二分探索で求める。
+    // -------------------------------------------------------------------------
+    pub fn fragment_at_line(&self, line: usize) -> Option<usize> {
+        let pos = self.fragments.partition_point(|f| f.end <= line);
+
+        match self.fragments.get(pos) {
+            Some(f) if f.start <= line => Some(pos),
+            _ => None,
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // text
+    // -------------------------------------------------------------------------
+    //
+    // index 番目のフラグメントの本文（行を連結したもの）を返す。
+    // -------------------------------------------------------------------------
+    pub fn text(&self, state: &AppState, index: usize) -> Option<String> {
+        self.get(index).map(|f| state.lines[f.start..f.end].concat())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// check_boundaries
+// -----------------------------------------------------------------------------
+//
+// SplitStrategy::plan_boundaries の戻り値が約束を守っているか確かめる：
+//
+//   - from_line より大きく、狭義単調増加であること
+//   - line_count 以下で、最後の境界が line_count であること
+//     （from_line が末尾なら空でよい）
+// -----------------------------------------------------------------------------
+fn check_boundaries(
+    boundaries: &[usize],
+    from_line: usize,
+    line_count: usize,
+) -> Result<()> {
+    let mut prev = from_line;
+
+    for &boundary in boundaries {
+        if boundary <= prev || boundary > line_count {
+            bail!(
+                "分割戦略が不正な境界を返しました: {}（{} より大きく {} 以下で、増えていく必要があります）",
+                boundary,
+                prev,
+                line_count
+            );
+        }
+        prev = boundary;
+    }
+
+    if prev != line_count {
+        bail!(
+            "分割戦略の境界が末尾まで届いていません（最後の境界 {}、行数 {}）",
+            prev,
+            line_count
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// ============================================================================
+// src/app/preview.rs
+// ============================================================================
+//
+// このファイルでは、取り込む前のフラグメントを確認するためのプレビュー
+// （先頭と末尾の数行）を組み立てる純粋ロジックを提供する。
+//
+// プロンプトには単位数しか出ないため、何を取り込もうとしているのかを
+// 行番号付きで見せることで「見ずにコピーする」ことを防ぐ。
+// 全文を確認したい場合は V(iew) で $PAGER を開く（external.rs）。
+// ============================================================================
+
+use std::ops::Range;
+
+/// 1 行のプレビューに表示する最大文字数
+const MAX_PREVIEW_CHARS: usize = 100;
+
+// -----------------------------------------------------------------------------
+// format_preview
+// -----------------------------------------------------------------------------
+//
+// range の行のうち、先頭 head 行と末尾 tail 行を行番号付きで整形する。
+// 途中を省略した場合は省略行数を表示する。
+//
+// 例（head = 2, tail = 2）：
+//     120 | fn main() {
+//     121 |     let x = 1;
+//         | ... (225 行省略)
+//     347 |     println!("{}", x);
+//     348 | }
+// -----------------------------------------------------------------------------
+pub fn format_preview(
+    lines: &[String],
+    range: &Range<usize>,
+    head: usize,
+    tail: usize,
+) -> String {
+    let range = range.start.min(lines.len())..range.end.min(lines.len());
+    let width = range.end.to_string().len();
+
+    if range.len() <= head + tail {
+        return range.map(|i| format_line(lines, i, width)).collect();
+    }
+
+    let omitted = range.len() - head - tail;
+    let mut out: String = (range.start..range.start + head)
+        .map(|i| format_line(lines, i, width))
+        .collect();
+    out.push_str(&format!(
+        "  {:>width$} | ... ({} 行省略)\n",
+        "",
+        omitted,
+        width = width
+    ));
+    out.extend(
+        (range.end - tail..range.end).map(|i| format_line(lines, i, width)),
+    );
+
+    out
+}
+
+// -----------------------------------------------------------------------------
+// format_line
+// -----------------------------------------------------------------------------
+//
+// 1 行を「行番号 | 本文」の形に整形する。
+// 改行は取り除き、長すぎる行は MAX_PREVIEW_CHARS 文字で切り詰める。
+// -----------------------------------------------------------------------------
+fn format_line(lines: &[String], index: usize, width: usize) -> String {
+    let text = lines[index].trim_end_matches(['\r', '\n']);
+    let shown: String = text.chars().take(MAX_PREVIEW_CHARS).collect();
+    let ellipsis =
+        if text.chars().count() > MAX_PREVIEW_CHARS { "…" } else { "" };
+
+    format!("  {:>width$} | {}{}\n", index + 1, shown, ellipsis, width = width)
+}
@@ -0,0 +1,213 @@
+// ============================================================================
+// src/app/prompter.rs
+// ============================================================================
+//
+// このファイルでは、App がユーザの入力を受け取るための Prompter を定義する。
+//
+// App は TTY を直接読まず、Prompter を通して入力を受け取る。
+// 通常は TTY から読む TtyPrompter を使い、テストでは入力列をあらかじめ
+// 用意した実装を差し込める（App::with_prompter）。
+//
+// 端末なしで動かすための実装として、ScriptPrompter（--script）と
+// AutoAdvancePrompter（--yes N）も提供する。
+//
+// --clear-after / --idle-timeout のため、App は読む前に wait_for_input で
+// 時間を区切って入力を待つ。待てない実装は、すぐに true を返せばよい。
+// ============================================================================
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use super::tty::{
+    read_choice_from_tty, read_line_from_tty, wait_for_tty_input,
+};
+
+// -----------------------------------------------------------------------------
+// Prompter
+// -----------------------------------------------------------------------------
+pub trait Prompter {
+    // -------------------------------------------------------------------------
+    // read_choice
+    // -------------------------------------------------------------------------
+    //
+    // プロンプトに対する選択を 1 つ読み、コマンド文字列として返す。
+    // 空文字列は「プロンプトのデフォルト」を表す。
+    //
+    // line_starters は 1 キー入力の実装向けで、続きを行入力で読むべき
+    // 先頭キーの一覧（KeyMap::line_starters）。
+    // -------------------------------------------------------------------------
+    fn read_choice(&mut self, line_starters: &[char]) -> Result<String>;
+
+    // -------------------------------------------------------------------------
+    // read_line
+    // -------------------------------------------------------------------------
+    //
+    // 自由入力を 1 行読む（検索後のフラグメント番号など）。
+    // -------------------------------------------------------------------------
+    fn read_line(&mut self) -> Result<String>;
+
+    // -------------------------------------------------------------------------
+    // wait_for_input
+    // -------------------------------------------------------------------------
+    //
+    // 入力が届くまで最大 timeout だけ待ち、届いたかどうかを返す。
+    // false なら App は時間切れの処理（clipboard のクリアなど）をして
+    // 待ち直す。既定では待たずに true を返す。
+    // -------------------------------------------------------------------------
+    fn wait_for_input(&mut self, _timeout: Duration) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// TtyPrompter
+// -----------------------------------------------------------------------------
+//
+// TTY（/dev/tty・CONIN$）から入力を読む Prompter。
+// single_key が true なら、選択は Enter を待たずに 1 キーで確定する。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TtyPrompter {
+    pub single_key: bool,
+}
+
+impl TtyPrompter {
+    pub fn new(single_key: bool) -> Self {
+        Self { single_key }
+    }
+}
+
+impl Prompter for TtyPrompter {
+    fn read_choice(&mut self, line_starters: &[char]) -> Result<String> {
+        if self.single_key {
+            read_choice_from_tty(line_starters)
+        } else {
+            read_line_from_tty()
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        read_line_from_tty()
+    }
+
+    fn wait_for_input(&mut self, timeout: Duration) -> Result<bool> {
+        wait_for_tty_input(timeout, self.single_key)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ScriptPrompter
+// -----------------------------------------------------------------------------
+//
+// あらかじめ与えたコマンド列を順に返す Prompter（--script）。
+// スクリプトやエディタのマクロから、端末なしで clip_frag を動かすために使う。
+//
+//   - from_commands: "y,y,p,q" のようなカンマ区切り
+//   - from_lines:    1 行 1 コマンド（空行は Enter、# で始まる行は無視）
+//
+// コマンドを使い切ると、選択では ScriptExhausted エラーを返し、App は
+// それを Quit として扱う（quit のキーを設定で変えていても終わるように、
+// "q" などの文字列は返さない）。自由入力では空行（中止）を返す。
+// 読んだコマンドは、ログで追えるよう標準エラー出力にエコーする。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Default)]
+pub struct ScriptPrompter {
+    commands: VecDeque<String>,
+}
+
+impl ScriptPrompter {
+    pub fn from_commands(script: &str) -> Self {
+        Self {
+            commands: script
+                .split(',')
+                .map(|c| c.trim().to_string())
+                .collect(),
+        }
+    }
+
+    pub fn from_lines(text: &str) -> Self {
+        Self {
+            commands: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// まだ返していないコマンドの数
+    pub fn remaining(&self) -> usize {
+        self.commands.len()
+    }
+
+    fn next_command(&mut self) -> Option<String> {
+        let command = self.commands.pop_front();
+        eprintln!("{}", command.as_deref().unwrap_or("(end of script)"));
+        command
+    }
+}
+
+impl Prompter for ScriptPrompter {
+    fn read_choice(&mut self, _line_starters: &[char]) -> Result<String> {
+        self.next_command().ok_or_else(|| ScriptExhausted.into())
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        Ok(self.next_command().unwrap_or_default())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ScriptExhausted
+// -----------------------------------------------------------------------------
+//
+// ScriptPrompter のコマンドを使い切ったことを表すエラー。
+// App はこれを受け取ると、Quit が入力されたものとして終了する。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptExhausted;
+
+impl fmt::Display for ScriptExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "スクリプトのコマンドを使い切りました")
+    }
+}
+
+impl std::error::Error for ScriptExhausted {}
+
+// -----------------------------------------------------------------------------
+// AutoAdvancePrompter
+// -----------------------------------------------------------------------------
+//
+// interval ごとに自動で Enter（各プロンプトのデフォルト）を返す Prompter
+// （--yes N）。main_loop では次のフラグメントを取り込み、最後の
+// exit_loop ではデフォルトの Quit で終わる。
+//
+// 検索後の番号入力などの自由入力には、待たずに空行（中止）を返す。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy)]
+pub struct AutoAdvancePrompter {
+    interval: Duration,
+}
+
+impl AutoAdvancePrompter {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+}
+
+impl Prompter for AutoAdvancePrompter {
+    fn read_choice(&mut self, _line_starters: &[char]) -> Result<String> {
+        std::thread::sleep(self.interval);
+        eprintln!("(auto)");
+        Ok(String::new())
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        Ok(String::new())
+    }
+}
@@ -0,0 +1,61 @@
+// ============================================================================
+// src/app/search.rs
+// ============================================================================
+//
+// このファイルでは、入力データを正規表現で検索し、一致した行が
+// どのフラグメントに含まれるかを求める純粋ロジックを提供する。
+//
+// 「fn finalize_loop があった部分をもう一度送りたい」といった場合に、
+// /pattern で検索して該当フラグメントへ移動できるようにする。
+// ============================================================================
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use super::plan::FragmentPlan;
+
+// -----------------------------------------------------------------------------
+// SearchHit
+// -----------------------------------------------------------------------------
+//
+// 検索で一致した 1 行。index はいずれも 0 始まり。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// 一致した行の index
+    pub line: usize,
+
+    /// その行を含むフラグメントの index
+    pub fragment: usize,
+}
+
+// -----------------------------------------------------------------------------
+// search_lines
+// -----------------------------------------------------------------------------
+//
+// lines を先頭から検索し、pattern に一致する行を返す。
+// pattern は正規表現（regex crate の構文）として解釈する。
+//
+// エラー：
+//   pattern が正規表現として不正な場合。
+// -----------------------------------------------------------------------------
+pub fn search_lines(
+    lines: &[String],
+    plan: &FragmentPlan,
+    pattern: &str,
+) -> Result<Vec<SearchHit>> {
+    let re = Regex::new(pattern)
+        .with_context(|| format!("invalid pattern: {}", pattern))?;
+
+    let hits = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| re.is_match(line))
+        .filter_map(|(line, _)| {
+            plan.fragment_at_line(line)
+                .map(|fragment| SearchHit { line, fragment })
+        })
+        .collect();
+
+    Ok(hits)
+}
@@ -0,0 +1,333 @@
+// ============================================================================
+// src/app/serve.rs
+// ============================================================================
+//
+// `clip_frag serve` の本体。
+//
+// 手元のマシンで TCP（既定は 127.0.0.1:7878）または Unix ドメインソケットで
+// 待ち受け、リモートの clip_frag（--clipboard remote:...）から届いた
+// フラグメントを、手元の clipboard（ClipboardSink）に設定する。
+//
+// 通信手順は clipboard/remote.rs を参照。
+// 接続は 1 つずつ順に処理する（clipboard は 1 つしかないため）。
+// つないだまま何も送らない（または 1 バイトずつゆっくり送る）相手に
+// 待ち受けを止められないよう、あいさつ全体が HELLO_TIMEOUT 以内に
+// 届かなければ切断する。
+// 1 つの接続でエラーが起きても、serve 自体は待ち受けを続ける。
+// ============================================================================
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use super::clipboard::remote::{
+    read_line, Endpoint, Stream, MAX_LINE, MAX_PAYLOAD, PROTOCOL,
+};
+use super::clipboard::ClipboardSink;
+use super::fragment::format_with_underscore;
+
+/// 接続からあいさつ（1 行全体）が届くまで待つ時間の上限
+pub const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+// -----------------------------------------------------------------------------
+// Listener
+// -----------------------------------------------------------------------------
+//
+// TCP / Unix ドメインソケットの待ち受けをまとめて扱う。
+// -----------------------------------------------------------------------------
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl Listener {
+    // -------------------------------------------------------------------------
+    // Listener::bind
+    // -------------------------------------------------------------------------
+    //
+    // Unix ドメインソケットは、前回のソケットファイルが残っていれば消してから
+    // 作り、本人だけが読み書きできるようにする（0600）。
+    // -------------------------------------------------------------------------
+    pub fn bind(endpoint: &Endpoint) -> Result<Self> {
+        match endpoint {
+            Endpoint::Tcp(addr) => {
+                let listener = TcpListener::bind(addr).with_context(|| {
+                    format!("{} で待ち受けできません", addr)
+                })?;
+                Ok(Self::Tcp(listener))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+                if std::fs::metadata(path)
+                    .is_ok_and(|m| m.file_type().is_socket())
+                {
+                    std::fs::remove_file(path)?;
+                }
+
+                let listener = std::os::unix::net::UnixListener::bind(path)
+                    .with_context(|| {
+                        format!("{} で待ち受けできません", endpoint)
+                    })?;
+                std::fs::set_permissions(
+                    path,
+                    std::fs::Permissions::from_mode(0o600),
+                )?;
+                Ok(Self::Unix(listener))
+            }
+            #[cfg(not(unix))]
+            Endpoint::Unix(_) => {
+                bail!("Unix ドメインソケットはこの OS では使えません")
+            }
+        }
+    }
+
+    /// 待ち受けているアドレス（ポート 0 を指定した場合の確認用）
+    pub fn local_endpoint(&self) -> Result<Endpoint> {
+        match self {
+            Self::Tcp(listener) => {
+                Ok(Endpoint::Tcp(listener.local_addr()?.to_string()))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let addr = listener.local_addr()?;
+                let path =
+                    addr.as_pathname().context("名前のないソケットです")?;
+                Ok(Endpoint::Unix(path.to_path_buf()))
+            }
+        }
+    }
+
+    /// 次の接続を待つ
+    pub fn accept(&self) -> Result<Box<dyn Stream>> {
+        match self {
+            Self::Tcp(listener) => Ok(Box::new(listener.accept()?.0)),
+            #[cfg(unix)]
+            Self::Unix(listener) => Ok(Box::new(listener.accept()?.0)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// serve
+// -----------------------------------------------------------------------------
+//
+// listener で待ち受けを続け、接続ごとに serve_connection を呼ぶ。
+// 戻るのは待ち受け自体が失敗したときだけ。
+// -----------------------------------------------------------------------------
+pub fn serve(
+    listener: &Listener,
+    token: &str,
+    sink: &mut dyn ClipboardSink,
+) -> Result<()> {
+    if token.is_empty() {
+        bail!("トークンが空です");
+    }
+
+    loop {
+        let stream = listener.accept()?;
+
+        if let Err(e) = serve_connection(stream, token, sink) {
+            eprintln!("接続を閉じました: {:#}", e);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// serve_connection
+// -----------------------------------------------------------------------------
+//
+// 1 つの接続を処理する。あいさつでトークンを確かめてから、
+// 接続が閉じられるまで SET / CLEAR を受け付ける。
+// トークンの不一致・不正なコマンドには ERR を返し、その接続だけを閉じる。
+// -----------------------------------------------------------------------------
+pub fn serve_connection(
+    stream: Box<dyn Stream>,
+    token: &str,
+    sink: &mut dyn ClipboardSink,
+) -> Result<()> {
+    let mut conn = BufReader::new(stream);
+
+    // ------------------------------------------------------------
+    // あいさつ（トークンの確認）
+    //   確認が済んだら、フラグメントの間で待てるよう上限を外す
+    // ------------------------------------------------------------
+    let hello = read_hello(&mut conn, Instant::now() + HELLO_TIMEOUT)
+        .context("あいさつが届きません")?;
+    let given = hello
+        .strip_prefix(PROTOCOL)
+        .and_then(|rest| rest.strip_prefix(' '))
+        .unwrap_or("");
+
+    if !tokens_match(given, token) {
+        return reject(&mut conn, "トークンが一致しません".into());
+    }
+    conn.get_ref().set_read_timeout(None)?;
+    reply(&mut conn, Ok(()))?;
+
+    // ------------------------------------------------------------
+    // コマンド
+    // ------------------------------------------------------------
+    loop {
+        let command = match read_line(&mut conn) {
+            Ok(command) => command,
+            // 相手が接続を閉じたら正常終了
+            Err(_) => return Ok(()),
+        };
+
+        let result = if let Some(len) = command.strip_prefix("SET ") {
+            let len = match len.parse::<usize>() {
+                Ok(len) if len <= MAX_PAYLOAD => len,
+                Ok(len) => {
+                    return reject(
+                        &mut conn,
+                        format!("データが大きすぎます（{} bytes）", len),
+                    )
+                }
+                Err(_) => {
+                    return reject(
+                        &mut conn,
+                        "SET のバイト数が不正です".into(),
+                    )
+                }
+            };
+
+            let mut body = vec![0u8; len];
+            conn.read_exact(&mut body)?;
+
+            match String::from_utf8(body) {
+                Ok(text) => sink.set_text(&text).map(|()| {
+                    eprintln!(
+                        "受信: {} bytes",
+                        format_with_underscore(text.len())
+                    );
+                }),
+                Err(_) => Err(anyhow::anyhow!("UTF-8 ではありません")),
+            }
+        } else if command == "CLEAR" {
+            sink.clear().map(|()| eprintln!("クリアしました。"))
+        } else {
+            return reject(
+                &mut conn,
+                format!("不明なコマンドです: {}", command),
+            );
+        };
+
+        reply(&mut conn, result.map_err(|e| format!("{:#}", e)))?;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// read_hello
+// -----------------------------------------------------------------------------
+//
+// あいさつの 1 行（最大 MAX_LINE バイト）を deadline までに読み、末尾の改行を
+// 除いて返す。読むたびに残り時間を読み取りの上限にするため、少しずつ
+// 送ってくる相手も deadline で打ち切る。
+// -----------------------------------------------------------------------------
+fn read_hello(
+    conn: &mut BufReader<Box<dyn Stream>>,
+    deadline: Instant,
+) -> Result<String> {
+    let mut line = Vec::new();
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            bail!("時間切れです");
+        }
+        conn.get_ref().set_read_timeout(Some(remaining))?;
+
+        let buf = conn.fill_buf()?;
+        if buf.is_empty() {
+            bail!("接続が閉じられました");
+        }
+
+        let (chunk, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (&buf[..=i], true),
+            None => (buf, false),
+        };
+        let len = chunk.len();
+        line.extend_from_slice(chunk);
+        conn.consume(len);
+
+        if line.len() as u64 > MAX_LINE {
+            bail!("行が長すぎます");
+        }
+        if done {
+            break;
+        }
+    }
+
+    let line = String::from_utf8(line).context("UTF-8 ではありません")?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// -----------------------------------------------------------------------------
+// reply
+// -----------------------------------------------------------------------------
+//
+// OK / ERR <理由> を 1 行で返す。理由に改行が含まれていれば空白にする。
+// -----------------------------------------------------------------------------
+fn reply(
+    conn: &mut BufReader<Box<dyn Stream>>,
+    result: std::result::Result<(), String>,
+) -> Result<()> {
+    let line = match result {
+        Ok(()) => "OK\n".to_string(),
+        Err(reason) => format!("ERR {}\n", reason.replace('\n', " ")),
+    };
+
+    let stream = conn.get_mut();
+    stream.write_all(line.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// reject
+// -----------------------------------------------------------------------------
+//
+// ERR <理由> を返し、接続を閉じるためのエラーにする。
+// -----------------------------------------------------------------------------
+fn reject(
+    conn: &mut BufReader<Box<dyn Stream>>,
+    reason: String,
+) -> Result<()> {
+    reply(conn, Err(reason.clone()))?;
+    bail!("接続を拒否しました: {}", reason)
+}
+
+// -----------------------------------------------------------------------------
+// tokens_match
+// -----------------------------------------------------------------------------
+//
+// トークンを比べる。一致する長さから推測されないよう、途中で打ち切らずに
+// 最後まで比べる。
+// -----------------------------------------------------------------------------
+fn tokens_match(given: &str, expected: &str) -> bool {
+    let (a, b) = (given.as_bytes(), expected.as_bytes());
+
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+
+    diff == 0 && a.len() == b.len()
+}
+
+// -----------------------------------------------------------------------------
+// generate_token
+// -----------------------------------------------------------------------------
+//
+// CLIP_FRAG_TOKEN が未設定のときに使う、推測されにくいトークン（32 桁の
+// 16 進数）。OS の暗号論的乱数（getrandom）から作る。
+// -----------------------------------------------------------------------------
+pub fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| anyhow::anyhow!("乱数を取得できません: {}", e))?;
+
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
@@ -11,6 +11,8 @@
 // 責務を明確にするため、ロジックはここには書かず、純粋なデータ構造だけを定義する。
 // ============================================================================
 
+use std::ops::Range;
+
 // -----------------------------------------------------------------------------
 // データ量の単位を表す列挙型
 // -----------------------------------------------------------------------------
@@ -20,12 +22,22 @@
 //
 // CLI の -c / -b オプションに対応する。
 // -----------------------------------------------------------------------------
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Unit {
     Chars,
     Bytes,
 }
 
+impl Unit {
+    /// text の単位数（文字数 または UTF-8 のバイト数）を数える
+    pub fn count(&self, text: &str) -> usize {
+        match self {
+            Unit::Chars => text.chars().count(),
+            Unit::Bytes => text.len(),
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // AppState 構造体
 // -----------------------------------------------------------------------------
@@ -55,6 +67,16 @@ pub struct AppState {
     /// 直前に取り込んだデータ
     pub prev_contents: String,
 
+    /// prev_contents がフラグメントか（ヘッダ・フッタなら false）。
+    /// --html でも、ヘッダ・フッタの取り込み直しはテキストのままにする
+    pub prev_is_fragment: bool,
+
+    /// これまでに取り込んだフラグメントの行範囲（取り込んだ順）
+    pub history: Vec<Range<usize>>,
+
+    /// S(kip) で取り込まずに読み飛ばしたフラグメントの行範囲
+    pub skipped: Vec<Range<usize>>,
+
     /// 次に取り込むべき行 index（行単位）
     pub curr_index: usize,
 
@@ -88,13 +110,7 @@ impl AppState {
         // ------------------------------------------------------------
         // 各行の単位数（chars または bytes）を計算する
         // ------------------------------------------------------------
-        let line_units: Vec<usize> = lines
-            .iter()
-            .map(|line| match unit {
-                Unit::Chars => line.chars().count(),
-                Unit::Bytes => line.len(),
-            })
-            .collect();
+        let line_units = count_line_units(&lines, unit);
 
         // ------------------------------------------------------------
         // 入力データ全体の単位数を計算する
@@ -103,9 +119,14 @@ impl AppState {
 
         // ------------------------------------------------------------
         // prev_contents は初期状態では空
-        // （ファイル指定時は App::new 側でヘッダを設定する）
+        // （ファイル指定時は App::run の copy_header がヘッダを設定する）
         // ------------------------------------------------------------
         let prev_contents = String::new();
+        let prev_is_fragment = false;
+
+        // 取り込み履歴・スキップ履歴は空から開始
+        let history = Vec::new();
+        let skipped = Vec::new();
 
         // curr_index は 0 から開始
         let curr_index = 0;
@@ -118,11 +139,41 @@ impl AppState {
             max_unit,
             unit,
             prev_contents,
+            prev_is_fragment,
+            history,
+            skipped,
             curr_index,
             from_file,
             input_file_name,
         }
     }
+
+    // -------------------------------------------------------------------------
+    // AppState::set_unit
+    // -------------------------------------------------------------------------
+    //
+    // セッション途中で単位・最大データ量を変更する。
+    // 単位が変わると各行の単位数と全体の単位数も変わるため、計算し直す。
+    // -------------------------------------------------------------------------
+    pub fn set_unit(&mut self, unit: Unit, max_unit: usize) {
+        if unit != self.unit {
+            self.line_units = count_line_units(&self.lines, unit);
+            self.total_units = self.line_units.iter().sum();
+            self.unit = unit;
+        }
+
+        self.max_unit = max_unit;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// 各行の単位数を計算するユーティリティ関数
+// -----------------------------------------------------------------------------
+//
+// 数え方は Unit::count に従う。
+// -----------------------------------------------------------------------------
+fn count_line_units(lines: &[String], unit: Unit) -> Vec<usize> {
+    lines.iter().map(|line| unit.count(line)).collect()
 }
 
 // -----------------------------------------------------------------------------
@@ -0,0 +1,108 @@
+// ============================================================================
+// src/app/summary.rs
+// ============================================================================
+//
+// このファイルでは、セッション終了時に表示する「まとめ」を作るための
+// 純粋ロジックを提供する。
+//
+// 最後まで一度も clipboard に取り込まれなかった行範囲（S(kip) で読み飛ばした
+// もの、F / N・G・last などの移動で飛ばしたもの、途中で終了して残ったもの）を
+// 求め、"L120–L348" の形式で表示する。
+// また、各フラグメントの送信状況（送信済み / 未送信 / スキップ）も判定する。
+// ============================================================================
+
+use std::ops::Range;
+
+// -----------------------------------------------------------------------------
+// never_sent_ranges
+// -----------------------------------------------------------------------------
+//
+// 全 line_count 行のうち、history（取り込んだ範囲）のどれにも含まれない
+// 行を、連続する行範囲にまとめて返す。
+//
+// どうやって飛ばしたか（スキップ・移動）は問わない。
+// 後から移動して取り込み直した行は「送信済み」として扱う。
+// -----------------------------------------------------------------------------
+pub fn never_sent_ranges(
+    line_count: usize,
+    history: &[Range<usize>],
+) -> Vec<Range<usize>> {
+    let mut sent = vec![false; line_count];
+    for r in history {
+        for flag in &mut sent[r.start.min(line_count)..r.end.min(line_count)] {
+            *flag = true;
+        }
+    }
+
+    // 連続する未送信行を 1 つの範囲にまとめる
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (i, _) in sent.iter().enumerate().filter(|(_, s)| !**s) {
+        match ranges.last_mut() {
+            Some(last) if last.end == i => last.end = i + 1,
+            _ => ranges.push(i..i + 1),
+        }
+    }
+
+    ranges
+}
+
+// -----------------------------------------------------------------------------
+// format_line_range
+// -----------------------------------------------------------------------------
+//
+// 行範囲（0 始まり・半開区間）を 1 始まりの "L120–L348" 形式にする。
+// 1 行だけの場合は "L120" とする。
+// -----------------------------------------------------------------------------
+pub fn format_line_range(range: &Range<usize>) -> String {
+    if range.end <= range.start + 1 {
+        format!("L{}", range.start + 1)
+    } else {
+        format!("L{}–L{}", range.start + 1, range.end)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// FragmentStatus
+// -----------------------------------------------------------------------------
+//
+// フラグメントの送信状況。TUI のフラグメント一覧などで使う。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentStatus {
+    /// clipboard に取り込み済み
+    Sent,
+
+    /// 未取り込み（まだ提示していない）
+    Pending,
+
+    /// S(kip) で読み飛ばしたまま未取り込み
+    Skipped,
+}
+
+// -----------------------------------------------------------------------------
+// fragment_status
+// -----------------------------------------------------------------------------
+//
+// range のフラグメントの送信状況を判定する。never_sent は never_sent_ranges
+// の結果で、まとめと同じく行ごとに取り込んだかどうかで判定する
+// （何回かに分けて取り込んだ行も送信済みになる）。
+//   - range と重なる never_sent がなければ Sent
+//   - そうでなく、range と重なる skipped があれば Skipped
+//   - それ以外は Pending
+// -----------------------------------------------------------------------------
+pub fn fragment_status(
+    range: &Range<usize>,
+    never_sent: &[Range<usize>],
+    skipped: &[Range<usize>],
+) -> FragmentStatus {
+    let overlaps =
+        |r: &Range<usize>| r.start < range.end && range.start < r.end;
+
+    if !never_sent.iter().any(overlaps) {
+        FragmentStatus::Sent
+    } else if skipped.iter().any(overlaps) {
+        FragmentStatus::Skipped
+    } else {
+        FragmentStatus::Pending
+    }
+}
@@ -16,9 +16,16 @@
 //   cat huge.txt | clip_frag
 //
 // 標準入力は huge.txt の内容で埋まっているが、ユーザの操作は TTY から受け取れる。
+//
+// また、Enter を押さずに 1 キーで選択を確定する「1 キー入力モード」のための
+// read_key_from_tty() / read_choice_from_tty() も提供する。
+// Unix 系 OS では termios で /dev/tty を一時的に非カノニカルモードにする。
+//
+// --clear-after / --idle-timeout のために、入力が届くまで時間を区切って
+// 待つ wait_for_tty_input() も提供する（Unix 系 OS のみ）。
 // ============================================================================
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -44,7 +51,7 @@ pub fn read_line_from_tty() -> Result<String> {
         // /dev/tty は「現在の端末」を表す特殊ファイルで、
         // 標準入力がパイプで埋まっていても、ユーザのキー入力を受け取れる。
         //
-        let file = File::open("/dev/tty")?;
+        let file = open_tty()?;
         let mut reader = BufReader::new(file);
 
         let mut buf = String::new();
@@ -62,7 +69,7 @@ pub fn read_line_from_tty() -> Result<String> {
         // これは「コンソール入力デバイス」を表す特殊ファイルで、
         // 標準入力がパイプで埋まっていても、ユーザのキー入力を受け取れる。
         //
-        let file = File::open("CONIN$")?;
+        let file = open_tty()?;
         let mut reader = BufReader::new(file);
 
         let mut buf = String::new();
@@ -70,3 +77,443 @@ pub fn read_line_from_tty() -> Result<String> {
         Ok(buf)
     }
 }
+
+// -----------------------------------------------------------------------------
+// open_tty / tty_available
+// -----------------------------------------------------------------------------
+//
+// OS ごとの TTY デバイス（/dev/tty・CONIN$）を読み込み用に開く。
+//
+// cron・CI・エディタのマクロなど、制御端末のない環境では開けない。
+// その場合は OS のエラーだけでなく、非対話モードの使い方も案内する。
+// -----------------------------------------------------------------------------
+#[cfg(unix)]
+pub const TTY_DEVICE: &str = "/dev/tty";
+
+#[cfg(windows)]
+pub const TTY_DEVICE: &str = "CONIN$";
+
+pub fn open_tty() -> Result<File> {
+    File::open(TTY_DEVICE).with_context(|| {
+        format!(
+            "端末（{}）を開けません。端末のない環境では --script または --yes で実行してください",
+            TTY_DEVICE
+        )
+    })
+}
+
+/// TTY デバイスを開けるかどうか
+pub fn tty_available() -> bool {
+    File::open(TTY_DEVICE).is_ok()
+}
+
+// -----------------------------------------------------------------------------
+// Key
+// -----------------------------------------------------------------------------
+//
+// 1 回のキー押下。read_key_from_tty() が返す。
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Esc,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// 上記以外（ファンクションキーなど）
+    Other,
+}
+
+// -----------------------------------------------------------------------------
+// parse_key
+// -----------------------------------------------------------------------------
+//
+// 1 回のキー押下で端末から届いたバイト列を Key に変換する。
+//
+//   - "\r" / "\n"          → Enter
+//   - 0x7f / 0x08          → Backspace
+//   - ESC 単独             → Esc
+//   - ESC [ A〜D / ESC O A〜D → 矢印キー
+//   - その他の ESC で始まる列 → Other（PgUp の ESC [ 5 ~ など）
+//   - UTF-8 の 1 文字      → Char
+// -----------------------------------------------------------------------------
+pub fn parse_key(bytes: &[u8]) -> Key {
+    match bytes {
+        [b'\r' | b'\n', ..] => Key::Enter,
+        [0x7f | 0x08] => Key::Backspace,
+        [0x1b] => Key::Esc,
+        [0x1b, b'[' | b'O', code] => match code {
+            b'A' => Key::Up,
+            b'B' => Key::Down,
+            b'C' => Key::Right,
+            b'D' => Key::Left,
+            _ => Key::Other,
+        },
+        [0x1b, ..] => Key::Other,
+        _ => match std::str::from_utf8(bytes).ok().map(|s| s.chars()) {
+            Some(mut chars) => match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_control() => Key::Char(c),
+                _ => Key::Other,
+            },
+            None => Key::Other,
+        },
+    }
+}
+
+// -----------------------------------------------------------------------------
+// escape_sequence_complete
+// -----------------------------------------------------------------------------
+//
+// ESC で始まるバイト列が、1 つのキーのエスケープシーケンスとして
+// そろったかどうか。読み込み側は、そろうまで（または続きが届かなくなるまで）
+// 読み続ける。残りを次のキーとして読んでしまわないため。
+//
+//   - ESC [ ... : 0x40〜0x7E の終端バイトまで（ESC [ 5 ~、ESC [ 1 ; 5 A など）
+//   - ESC O x   : 3 バイト（ESC O A など）
+//   - ESC x     : 2 バイト（Alt + キー）
+// -----------------------------------------------------------------------------
+pub fn escape_sequence_complete(bytes: &[u8]) -> bool {
+    match bytes {
+        [0x1b] | [0x1b, b'[' | b'O'] => false,
+        [0x1b, b'[', .., last] => (0x40..=0x7e).contains(last),
+        _ => true,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// KeyInput
+// -----------------------------------------------------------------------------
+//
+// 1 キー入力モードで、押されたキーをどう扱うか。
+//   - Immediate: その場でコマンドとして確定する（"y" / "q" / "" など）
+//   - NeedsLine: 引数付きコマンドの先頭なので、続きを行単位で読む
+//   - Ignore:    無視して次のキーを待つ
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyInput {
+    Immediate(String),
+    NeedsLine(char),
+    Ignore,
+}
+
+// -----------------------------------------------------------------------------
+// key_to_input
+// -----------------------------------------------------------------------------
+//
+// キーを、行入力と同じ形のコマンド文字列に変換する。
+// どの文字列がどのコマンドになるかはキー割り当て（KeyMap）で決まる。
+//
+//   - Enter          → ""（各プロンプトのデフォルト）
+//   - Space          → "space"
+//   - ← / → / ↑ / ↓  → "left" / "right" / "up" / "down"
+//   - 数字・line_starters に含まれる文字
+//                    → 引数が必要なので行入力に切り替える
+//   - その他の文字   → その文字 1 つのコマンド
+//
+// line_starters は KeyMap::line_starters() の結果（小文字）を渡す。
+// -----------------------------------------------------------------------------
+pub fn key_to_input(key: Key, line_starters: &[char]) -> KeyInput {
+    let named = |name: &str| KeyInput::Immediate(name.to_string());
+
+    match key {
+        Key::Enter => KeyInput::Immediate(String::new()),
+        Key::Char(' ') => named("space"),
+        Key::Left => named("left"),
+        Key::Right => named("right"),
+        Key::Up => named("up"),
+        Key::Down => named("down"),
+        Key::Char(c)
+            if c.is_ascii_digit()
+                || line_starters.contains(&c.to_ascii_lowercase()) =>
+        {
+            KeyInput::NeedsLine(c)
+        }
+        Key::Char(c) => KeyInput::Immediate(c.to_string()),
+        Key::Backspace | Key::Esc | Key::Other => KeyInput::Ignore,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// read_choice_from_tty
+// -----------------------------------------------------------------------------
+//
+// 1 キー入力モード用の入力関数。Enter を待たずに 1 キーで選択を確定し、
+// read_line_from_tty() と同じ形のコマンド文字列を返す。
+//
+// 引数付きコマンド（/pattern・g K・m N など）の先頭キーが押された場合だけ、
+// 続きを通常の行入力（Enter で確定）で読み、先頭キーと連結して返す。
+// -----------------------------------------------------------------------------
+pub fn read_choice_from_tty(line_starters: &[char]) -> Result<String> {
+    loop {
+        match key_to_input(read_key_from_tty()?, line_starters) {
+            KeyInput::Immediate(input) => {
+                // raw mode ではエコーされないため、選択を表示して改行する
+                eprintln!("{}", input);
+                return Ok(input);
+            }
+            KeyInput::NeedsLine(first) => {
+                eprint!("{}", first);
+                let rest = read_line_from_tty()?;
+                return Ok(format!("{}{}", first, rest));
+            }
+            KeyInput::Ignore => {}
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// read_key_from_tty
+// -----------------------------------------------------------------------------
+//
+// TTY から 1 キー分の入力を読む。
+//
+//   - Unix 系 OS: /dev/tty を termios で非カノニカル・エコーなしに切り替えて
+//                 読み、読み終えたら元の設定に戻す（raw モジュール）。
+//   - Windows:    1 行読み、先頭の 1 文字をキーとみなす。
+// -----------------------------------------------------------------------------
+pub fn read_key_from_tty() -> Result<Key> {
+    #[cfg(unix)]
+    {
+        raw::read_key()
+    }
+
+    #[cfg(windows)]
+    {
+        let line = read_line_from_tty()?;
+        let line = line.trim_end_matches(['\r', '\n']);
+        Ok(match line.chars().next() {
+            None => Key::Enter,
+            Some(c) => Key::Char(c),
+        })
+    }
+}
+
+/// wait_for_tty_input で実際に入力を待てるか（Windows では待てない）
+pub const CAN_WAIT_FOR_INPUT: bool = cfg!(unix);
+
+// -----------------------------------------------------------------------------
+// wait_for_tty_input
+// -----------------------------------------------------------------------------
+//
+// TTY に入力が届くまで、最大 timeout だけ待つ。届けば true、届かなければ
+// false（シグナルで起こされた場合も false）。
+//
+// 1 キー入力モード（raw_mode）では、1 キーで起きられるよう非カノニカル
+// モードで待つ。行入力では Enter で 1 行がそろうまで起きない。
+// Windows では待たずに true を返す（そのまま読み込みで待つ）。
+// そのため Windows では --clear-after / --idle-timeout が働かない
+// （CAN_WAIT_FOR_INPUT を見て main が警告する）。
+// -----------------------------------------------------------------------------
+pub fn wait_for_tty_input(
+    timeout: std::time::Duration,
+    raw_mode: bool,
+) -> Result<bool> {
+    #[cfg(unix)]
+    {
+        raw::wait_readable(timeout, raw_mode)
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = (timeout, raw_mode);
+        Ok(true)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// raw（Unix 系 OS のみ）
+// -----------------------------------------------------------------------------
+//
+// /dev/tty の termios を一時的に変更して 1 キーずつ読むための実装。
+//
+// 端末設定を壊したまま終了しないよう、以下のすべてで元の設定に戻す：
+//   - 通常時: RawModeGuard の drop（panic による巻き戻しを含む）
+//   - シグナル: SIGINT / SIGTERM / SIGHUP / SIGQUIT のハンドラ
+//
+// ISIG は残すので、raw 中でも Ctrl-C で SIGINT が届く。ハンドラは設定を戻した後、
+// デフォルトの動作に戻してシグナルを送り直す（＝通常どおりプロセスが終了する）。
+// -----------------------------------------------------------------------------
+#[cfg(unix)]
+mod raw {
+    use super::{escape_sequence_complete, parse_key, Key};
+    use anyhow::{Context, Result};
+    use std::fs::{File, OpenOptions};
+    use std::io::Read;
+    use std::os::unix::io::AsRawFd;
+    use std::sync::{Once, OnceLock};
+
+    /// ESC の後に続くバイト（矢印キー等）を待つ時間（ミリ秒）
+    const ESCAPE_TIMEOUT_MS: libc::c_int = 30;
+
+    /// 1 つのエスケープシーケンスとして読む最大のバイト数
+    const ESCAPE_MAX_LEN: usize = 16;
+
+    /// 開きっぱなしにする /dev/tty（シグナルハンドラからも参照する）
+    static TTY: OnceLock<File> = OnceLock::new();
+
+    /// raw にする前の端末設定（シグナルハンドラからも参照する）
+    static ORIGINAL: OnceLock<libc::termios> = OnceLock::new();
+
+    static INSTALL_HANDLERS: Once = Once::new();
+
+    pub fn read_key() -> Result<Key> {
+        let tty = tty()?;
+        let _guard = RawModeGuard::enable(tty)?;
+
+        let mut bytes = vec![read_byte(tty)?];
+
+        if bytes[0] == 0x1b {
+            // エスケープシーケンスの続きが届いていれば、終わりまで読む
+            while !escape_sequence_complete(&bytes)
+                && bytes.len() < ESCAPE_MAX_LEN
+                && poll_readable(tty, ESCAPE_TIMEOUT_MS)
+            {
+                bytes.push(read_byte(tty)?);
+            }
+        } else {
+            // UTF-8 の多バイト文字なら残りのバイトを読む
+            for _ in 1..utf8_len(bytes[0]) {
+                bytes.push(read_byte(tty)?);
+            }
+        }
+
+        Ok(parse_key(&bytes))
+    }
+
+    pub fn wait_readable(
+        timeout: std::time::Duration,
+        raw_mode: bool,
+    ) -> Result<bool> {
+        let tty = tty()?;
+        let _guard =
+            if raw_mode { Some(RawModeGuard::enable(tty)?) } else { None };
+
+        let timeout_ms =
+            timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        Ok(poll_readable(tty, timeout_ms))
+    }
+
+    fn tty() -> Result<&'static File> {
+        if let Some(tty) = TTY.get() {
+            return Ok(tty);
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .context("端末（/dev/tty）を開けません。端末のない環境では --script または --yes で実行してください")?;
+        Ok(TTY.get_or_init(|| file))
+    }
+
+    fn read_byte(mut tty: &File) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        tty.read_exact(&mut buf).context("failed to read from /dev/tty")?;
+        Ok(buf[0])
+    }
+
+    fn poll_readable(tty: &File, timeout_ms: libc::c_int) -> bool {
+        let mut fds = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        // SAFETY: 有効な pollfd 1 つを渡している
+        unsafe { libc::poll(&mut fds, 1, timeout_ms) > 0 }
+    }
+
+    fn utf8_len(first: u8) -> usize {
+        match first {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // RawModeGuard
+    // -------------------------------------------------------------------------
+    //
+    // 生存中だけ端末を「非カノニカル・エコーなし」にする。
+    // -------------------------------------------------------------------------
+    struct RawModeGuard {
+        fd: libc::c_int,
+    }
+
+    impl RawModeGuard {
+        fn enable(tty: &File) -> Result<Self> {
+            let fd = tty.as_raw_fd();
+
+            // SAFETY: termios は C の構造体で、ゼロ初期化後に tcgetattr で埋める
+            let mut original: libc::termios = unsafe { std::mem::zeroed() };
+            if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .context("failed to get terminal attributes");
+            }
+
+            let original = *ORIGINAL.get_or_init(|| original);
+            INSTALL_HANDLERS.call_once(install_signal_handlers);
+
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+
+            // SAFETY: fd は開いている TTY、raw は有効な termios
+            if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .context("failed to set terminal attributes");
+            }
+
+            Ok(Self { fd })
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            if let Some(original) = ORIGINAL.get() {
+                // SAFETY: 保存しておいた元の設定に戻すだけ
+                unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, original) };
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // シグナルハンドラ
+    // -------------------------------------------------------------------------
+    //
+    // ハンドラ内では async-signal-safe な tcsetattr / signal / raise だけを使う。
+    // -------------------------------------------------------------------------
+    fn install_signal_handlers() {
+        for sig in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT] {
+            // SAFETY: ハンドラは extern "C" fn(c_int) で、安全な処理しか行わない
+            unsafe {
+                libc::signal(
+                    sig,
+                    restore_and_reraise as extern "C" fn(libc::c_int)
+                        as libc::sighandler_t,
+                );
+            }
+        }
+    }
+
+    extern "C" fn restore_and_reraise(sig: libc::c_int) {
+        if let (Some(tty), Some(original)) = (TTY.get(), ORIGINAL.get()) {
+            // SAFETY: async-signal-safe な関数のみを呼ぶ
+            unsafe {
+                libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, original);
+            }
+        }
+
+        // SAFETY: デフォルト動作に戻してシグナルを送り直す
+        unsafe {
+            libc::signal(sig, libc::SIG_DFL);
+            libc::raise(sig);
+        }
+    }
+}
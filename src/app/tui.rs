@@ -0,0 +1,510 @@
+// ============================================================================
+// src/app/tui.rs
+// ============================================================================
+//
+// このファイルでは、--tui 指定時の全画面 TUI モードを提供する。
+//
+// 行指向の Y/P/Q プロンプト（main_loop / finalize_loop / exit_loop）の代わりに、
+//
+//   - 左: フラグメント一覧（行範囲・単位数・送信状況）
+//   - 右: 選択中のフラグメントのプレビュー（スクロール可能）
+//   - 上: 進捗バー
+//   - 下: キー操作の案内・メッセージ・検索入力欄
+//
+// を 1 画面に表示し、1 キーで操作できるようにする。
+//
+// 分割計画・取り込み・スキップ・検索などのロジックは行指向モードと共通で、
+// ここでは画面描画とキー入力の割り当てだけを担当する。
+//
+// 描画は標準エラー出力に行う（行指向モードのプロンプトと同じ）。
+// キー入力は crossterm が TTY（標準入力がパイプなら /dev/tty）から読む。
+// ============================================================================
+
+use std::time::Instant;
+
+use anyhow::Result;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+
+use super::clipboard::set_text_verified;
+use super::command::TuiAction;
+use super::fragment::format_with_underscore;
+use super::plan::FragmentSpan;
+use super::search::search_lines;
+use super::summary::{
+    format_line_range, fragment_status, never_sent_ranges, FragmentStatus,
+};
+use super::{unit_label, App, Deadline, Outcome};
+
+/// PageUp / PageDown でプレビューをスクロールする行数
+const SCROLL_STEP: u16 = 10;
+
+// -----------------------------------------------------------------------------
+// TuiState
+// -----------------------------------------------------------------------------
+//
+// TUI 画面だけが持つ状態（選択位置・スクロール量・メッセージなど）。
+// 取り込み済み範囲などの本来の状態は AppState 側にある。
+// -----------------------------------------------------------------------------
+struct TuiState {
+    /// 一覧で選択中のフラグメント index
+    selected: usize,
+
+    /// プレビューのスクロール量（行）
+    scroll: u16,
+
+    /// 画面下部に表示するメッセージ
+    message: String,
+
+    /// 検索パターンの入力中なら Some（入力途中の文字列）
+    search_input: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// TerminalGuard
+// -----------------------------------------------------------------------------
+//
+// raw mode と代替画面を有効にし、drop 時に必ず元に戻す。
+// panic 時もメッセージが代替画面に消えないよう、panic hook で先に戻す。
+// -----------------------------------------------------------------------------
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        execute!(std::io::stderr(), EnterAlternateScreen)?;
+
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(std::io::stderr(), LeaveAlternateScreen);
+}
+
+impl App {
+    // ------------------------------------------------------------------------
+    // App::run_tui
+    // ------------------------------------------------------------------------
+    //
+    // 全画面 TUI モードのメインフロー。
+    // q / Esc / Ctrl-C で終了し、行指向モードの Quit と同じく
+    // clipboard を起動前の内容に戻して（なければクリアして）
+    // セッションのまとめを表示する。
+    // ------------------------------------------------------------------------
+    pub fn run_tui(&mut self) -> Result<Outcome> {
+        self.snapshot_clipboard();
+        self.copy_header()?;
+
+        let mut ui = TuiState {
+            selected: self
+                .next_fragment()
+                .min(self.plan.len().saturating_sub(1)),
+            scroll: 0,
+            message: String::new(),
+            search_input: None,
+        };
+
+        let mut idle_message = None;
+
+        {
+            let _guard = TerminalGuard::enter()?;
+            let mut terminal =
+                Terminal::new(CrosstermBackend::new(std::io::stderr()))?;
+
+            loop {
+                terminal.draw(|f| self.draw_tui(f, &ui))?;
+
+                // --clear-after / --idle-timeout の期限までだけ入力を待つ
+                match self.check_deadlines()? {
+                    Deadline::Cleared(message) => {
+                        ui.message = message;
+                        continue;
+                    }
+                    Deadline::Idle(message) => {
+                        idle_message = Some(message);
+                        break;
+                    }
+                    Deadline::Wait(Some(at)) => {
+                        let timeout =
+                            at.saturating_duration_since(Instant::now());
+                        if !event::poll(timeout)? {
+                            continue;
+                        }
+                    }
+                    Deadline::Wait(None) => {}
+                }
+
+                let Event::Key(key) = event::read()? else {
+                    continue;
+                };
+                self.last_input = Instant::now();
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+
+                if ui.search_input.is_some() {
+                    self.handle_search_key(&mut ui, key);
+                } else if !self.handle_tui_key(&mut ui, key) {
+                    break;
+                }
+            }
+        }
+
+        // ここで _guard が drop され、端末が元に戻ってから終了処理に進む
+        if let Some(message) = idle_message {
+            eprintln!("{}", message);
+        }
+        self.quit()
+    }
+
+    // ------------------------------------------------------------------------
+    // handle_tui_key
+    // ------------------------------------------------------------------------
+    //
+    // 通常時のキー操作。終了する場合は false を返す。
+    // キーは設定ファイルの [keys.tui]（TuiKeyMap）で割り当てる。
+    // Ctrl-C は割り当てに関係なく終了する。
+    // ------------------------------------------------------------------------
+    fn handle_tui_key(&mut self, ui: &mut TuiState, key: KeyEvent) -> bool {
+        let len = self.plan.len();
+        if key.code == KeyCode::Char('c')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            return false;
+        }
+
+        let Some(action) =
+            key_name(&key).and_then(|name| self.config.keys.tui.action(&name))
+        else {
+            return true;
+        };
+
+        match action {
+            TuiAction::Quit => return false,
+
+            // 選択中のフラグメントを取り込み、次を選択する
+            TuiAction::Copy if len > 0 => {
+                let index = ui.selected;
+                ui.message = match self.copy_fragment(index) {
+                    Ok(()) => {
+                        select(ui, index + 1, len);
+                        format!(
+                            "fragment {}/{} を取り込みました。",
+                            index + 1,
+                            len
+                        )
+                    }
+                    // 取り込めなかった場合は選択を動かさない
+                    Err(e) => format!("取り込みに失敗しました: {:#}", e),
+                };
+            }
+
+            // 取り込まずに次へ
+            TuiAction::Skip if len > 0 => {
+                let span = self.plan.fragments()[ui.selected];
+                self.state.skipped.push(span.start..span.end);
+                self.state.curr_index = span.end;
+                ui.message = format!(
+                    "fragment {}/{} をスキップしました。",
+                    ui.selected + 1,
+                    len
+                );
+                select(ui, ui.selected + 1, len);
+            }
+
+            TuiAction::Down => select(ui, ui.selected + 1, len),
+            TuiAction::Up => select(ui, ui.selected.saturating_sub(1), len),
+            TuiAction::First => select(ui, 0, len),
+            TuiAction::Last => select(ui, len.saturating_sub(1), len),
+
+            TuiAction::ScrollDown => {
+                ui.scroll = ui.scroll.saturating_add(SCROLL_STEP)
+            }
+            TuiAction::ScrollUp => {
+                ui.scroll = ui.scroll.saturating_sub(SCROLL_STEP)
+            }
+
+            TuiAction::Search => ui.search_input = Some(String::new()),
+
+            // フッタを取り込む（ファイル指定時のみ）
+            TuiAction::Footer if self.state.from_file => {
+                let footer = self.footer_text();
+                ui.message =
+                    match set_text_verified(&mut self.clipboard, &footer) {
+                        Ok(()) => {
+                            self.set_prev(footer, false);
+                            "フッタを取り込みました。".to_string()
+                        }
+                        Err(e) => format!("取り込みに失敗しました: {:#}", e),
+                    };
+            }
+
+            _ => {}
+        }
+
+        true
+    }
+
+    // ------------------------------------------------------------------------
+    // handle_search_key
+    // ------------------------------------------------------------------------
+    //
+    // 検索パターン入力中のキー操作。
+    // Enter で検索し、選択位置より後ろで最初に一致したフラグメントを選択する
+    // （見つからなければ先頭から探し直す）。Esc で中止。
+    // ------------------------------------------------------------------------
+    fn handle_search_key(&mut self, ui: &mut TuiState, key: KeyEvent) {
+        let Some(input) = ui.search_input.as_mut() else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc => ui.search_input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => {
+                let pattern = ui.search_input.take().unwrap_or_default();
+
+                ui.message = match search_lines(
+                    &self.state.lines,
+                    &self.plan,
+                    &pattern,
+                ) {
+                    Err(e) => format!("検索パターンが不正です: {:#}", e),
+                    Ok(hits) if hits.is_empty() => {
+                        format!("一致する行はありません: /{}", pattern)
+                    }
+                    Ok(hits) => {
+                        let hit = hits
+                            .iter()
+                            .find(|h| h.fragment > ui.selected)
+                            .unwrap_or(&hits[0]);
+                        let len = self.plan.len();
+                        select(ui, hit.fragment, len);
+                        format!(
+                            "/{}: {} 件一致（L{} を含む fragment {}/{}）",
+                            pattern,
+                            hits.len(),
+                            hit.line + 1,
+                            hit.fragment + 1,
+                            len
+                        )
+                    }
+                };
+            }
+            _ => {}
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // draw_tui
+    // ------------------------------------------------------------------------
+    //
+    // 画面全体を描画する。
+    // ------------------------------------------------------------------------
+    fn draw_tui(&self, f: &mut Frame, ui: &TuiState) {
+        let [top, body, bottom] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .areas(f.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Length(40), Constraint::Min(0)])
+                .areas(body);
+
+        // ------------------------------------------------------------
+        // 進捗バー（取り込み済みの単位数の割合）
+        // ------------------------------------------------------------
+        let unit = unit_label(self.state.unit);
+        let never_sent =
+            never_sent_ranges(self.state.lines.len(), &self.state.history);
+        let status_of = |s: &FragmentSpan| {
+            fragment_status(
+                &(s.start..s.end),
+                &never_sent,
+                &self.state.skipped,
+            )
+        };
+        let sent_units: usize = self
+            .plan
+            .fragments()
+            .iter()
+            .filter(|s| status_of(s) == FragmentStatus::Sent)
+            .map(|s| s.units)
+            .sum();
+        let ratio = if self.state.total_units == 0 {
+            0.0
+        } else {
+            sent_units as f64 / self.state.total_units as f64
+        };
+        let gauge = Gauge::default()
+            .block(Block::bordered().title(" clip_frag "))
+            .gauge_style(Style::default().fg(Color::Green))
+            .ratio(ratio.min(1.0))
+            .label(format!(
+                "{} / {} [{}] ({:.1} %)",
+                format_with_underscore(sent_units),
+                format_with_underscore(self.state.total_units),
+                unit,
+                ratio * 100.0
+            ));
+        f.render_widget(gauge, top);
+
+        // ------------------------------------------------------------
+        // フラグメント一覧
+        // ------------------------------------------------------------
+        let items: Vec<ListItem> = self
+            .plan
+            .fragments()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let (mark, color) = match status_of(s) {
+                    FragmentStatus::Sent => ("✓", Color::Green),
+                    FragmentStatus::Skipped => ("-", Color::Yellow),
+                    FragmentStatus::Pending => (" ", Color::Reset),
+                };
+                ListItem::new(format!(
+                    "{} {:>3} {:<14} {:>10}",
+                    mark,
+                    i + 1,
+                    format_line_range(&(s.start..s.end)),
+                    format_with_underscore(s.units)
+                ))
+                .style(Style::default().fg(color))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(
+                " fragments {}/{} ",
+                (ui.selected + 1).min(self.plan.len()),
+                self.plan.len()
+            )))
+            .highlight_style(
+                Style::default().add_modifier(Modifier::REVERSED),
+            );
+        let mut list_state =
+            ListState::default().with_selected(Some(ui.selected));
+        f.render_stateful_widget(list, left, &mut list_state);
+
+        // ------------------------------------------------------------
+        // プレビュー（選択中のフラグメント全体、行番号付き）
+        // ------------------------------------------------------------
+        let preview = match self.plan.get(ui.selected) {
+            Some(span) => {
+                let width = span.end.to_string().len();
+                let lines: Vec<Line> = (span.start..span.end)
+                    .map(|i| {
+                        let text =
+                            self.state.lines[i].trim_end_matches(['\r', '\n']);
+                        Line::from(vec![
+                            Span::styled(
+                                format!("{:>width$} │ ", i + 1, width = width),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            Span::raw(text.to_string()),
+                        ])
+                    })
+                    .collect();
+                Paragraph::new(Text::from(lines))
+                    .block(Block::bordered().title(format!(
+                        " {} ",
+                        format_line_range(&(span.start..span.end))
+                    )))
+                    .scroll((ui.scroll, 0))
+            }
+            None => Paragraph::new("（入力データがありません）")
+                .block(Block::bordered()),
+        };
+        f.render_widget(preview, right);
+
+        // ------------------------------------------------------------
+        // メッセージ・キー操作の案内（検索中は入力欄）
+        // ------------------------------------------------------------
+        let status = match &ui.search_input {
+            Some(input) => format!("/{}", input),
+            None => ui.message.clone(),
+        };
+        let help = Paragraph::new(vec![
+            Line::from(status),
+            Line::styled(
+                self.config.keys.tui.help_line(),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        f.render_widget(help, bottom);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// key_name
+// -----------------------------------------------------------------------------
+//
+// crossterm のキーを TuiKeyMap のキー名にする（"j" / "G" / "enter" など）。
+// 名前のないキーは None。
+// -----------------------------------------------------------------------------
+fn key_name(key: &KeyEvent) -> Option<String> {
+    let name = match key.code {
+        KeyCode::Char(' ') => "space",
+        KeyCode::Char(c) => return Some(c.to_string()),
+        KeyCode::Enter => "enter",
+        KeyCode::Esc => "esc",
+        KeyCode::Tab => "tab",
+        KeyCode::Backspace => "backspace",
+        KeyCode::Up => "up",
+        KeyCode::Down => "down",
+        KeyCode::Left => "left",
+        KeyCode::Right => "right",
+        KeyCode::Home => "home",
+        KeyCode::End => "end",
+        KeyCode::PageUp => "pageup",
+        KeyCode::PageDown => "pagedown",
+        _ => return None,
+    };
+
+    Some(name.to_string())
+}
+
+// -----------------------------------------------------------------------------
+// select
+// -----------------------------------------------------------------------------
+//
+// 選択位置を index に移動する（末尾を超えないよう丸める）。
+// 選択が変わったらプレビューのスクロールを先頭に戻す。
+// -----------------------------------------------------------------------------
+fn select(ui: &mut TuiState, index: usize, len: usize) {
+    let index = index.min(len.saturating_sub(1));
+    if index != ui.selected {
+        ui.selected = index;
+        ui.scroll = 0;
+    }
+}
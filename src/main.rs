@@ -12,10 +12,24 @@
 //!
 //! ============================================================================
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
 
-use clip_frag::app::{App, Unit};
+use clip_frag::app::clipboard::remote::{DEFAULT_ADDR, TOKEN_ENV};
+use clip_frag::app::clipboard::{
+    run_hold, Endpoint, Selection, DEFAULT_HOLD_TIMEOUT,
+};
+use clip_frag::app::config::Config;
+use clip_frag::app::encoding::detect_encoding;
+use clip_frag::app::serve::{generate_token, serve, Listener};
+use clip_frag::app::tty::{tty_available, CAN_WAIT_FOR_INPUT, TTY_DEVICE};
+use clip_frag::app::{
+    App, AutoAdvancePrompter, ClipboardBackend, ClipboardSink, Prompter,
+    ScriptPrompter, TtyPrompter, Unit,
+};
+
+/// これより確度が低い判定では、文字化けの注意を表示する
+const LOW_CONFIDENCE: f64 = 0.5;
 
 /// CLI オプション定義
 ///
@@ -32,8 +46,86 @@ pub struct Cli {
     #[arg(short = 'b', long = "bytes")]
     pub bytes: Option<usize>,
 
+    /// Enter を押さずに 1 キーで選択を確定する
+    #[arg(short = 'k', long = "single-key")]
+    pub single_key: bool,
+
+    /// 端末の代わりに、与えたコマンド列で操作する
+    /// （例: "y,y,p,q"。@FILE なら 1 行 1 コマンドのファイルを読む）
+    #[arg(long = "script", value_name = "COMMANDS", conflicts_with = "yes")]
+    pub script: Option<String>,
+
+    /// 端末の代わりに、N 秒ごとに自動で次のフラグメントを取り込む
+    #[arg(long = "yes", value_name = "SECONDS")]
+    pub yes: Option<u64>,
+
+    /// 取り込み先: system / osc52 / wl-copy / xclip / xsel / pbcopy / tmux /
+    /// command:CMD / file:PATH / remote:ADDR
+    /// （省略時は設定ファイル、なければ system）
+    #[arg(long = "clipboard", value_name = "BACKEND", global = true)]
+    pub clipboard: Option<ClipboardBackend>,
+
+    /// 書き込み先（X11 / Wayland）: clipboard / primary（中クリック）/ both
+    #[arg(long = "selection", value_name = "TARGET", global = true)]
+    pub selection: Option<Selection>,
+
+    /// K(eep) で終了した後、clipboard の内容を残しておく秒数（既定 600）
+    #[arg(long = "hold-timeout", value_name = "SECONDS")]
+    pub hold_timeout: Option<u64>,
+
+    /// Quit で起動前の clipboard の内容に戻さず、クリアする
+    #[arg(long = "clear-on-exit")]
+    pub clear_on_exit: bool,
+
+    /// 取り込んでから N 秒たったら clipboard をクリアする
+    #[arg(long = "clear-after", value_name = "SECONDS")]
+    pub clear_after: Option<u64>,
+
+    /// N 秒間操作がなければ、Quit と同じく終了する
+    #[arg(long = "idle-timeout", value_name = "SECONDS")]
+    pub idle_timeout: Option<u64>,
+
+    /// フラグメントを HTML（入力ファイルの拡張子で構文ハイライト）として取り込む
+    #[arg(long = "html")]
+    pub html: bool,
+
+    /// 設定ファイル（省略時は ~/.config/clip_frag/config.toml があれば読む）
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config: Option<std::path::PathBuf>,
+
+    /// 全画面 TUI モードで起動する
+    #[cfg(feature = "tui")]
+    #[arg(long = "tui")]
+    pub tui: bool,
+
+    /// 入力ファイルの代わりに、clipboard のテキストを分割する
+    #[arg(long = "from-clipboard", conflicts_with = "input_file")]
+    pub from_clipboard: bool,
+
     /// 入力ファイル名（省略時は標準入力）
     pub input_file: Option<std::path::PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// サブコマンド
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// リモートの clip_frag から届いたフラグメントを clipboard に設定する
+    Serve {
+        /// 待ち受けるアドレス（ADDR:PORT または unix:PATH）
+        #[arg(long = "listen", value_name = "ADDR", default_value = DEFAULT_ADDR)]
+        listen: Endpoint,
+    },
+
+    /// 標準入力の内容を、置き換えられるまで clipboard に保持する（内部用）
+    #[command(hide = true)]
+    Hold {
+        /// 保持する最大の秒数
+        #[arg(long = "timeout", value_name = "SECONDS", default_value_t = DEFAULT_HOLD_TIMEOUT)]
+        timeout: u64,
+    },
 }
 
 fn main() -> Result<()> {
@@ -42,6 +134,17 @@ fn main() -> Result<()> {
     // -------------------------------------------------------------------------
     let cli = Cli::parse();
 
+    match cli.command {
+        Some(Command::Serve { ref listen }) => return run_serve(&cli, listen),
+        Some(Command::Hold { timeout }) => {
+            return run_hold(
+                std::time::Duration::from_secs(timeout),
+                cli.selection.unwrap_or_default(),
+            );
+        }
+        None => {}
+    }
+
     // -------------------------------------------------------------------------
     // 2. 最大データ量の決定
     //    chars / bytes のどちらかが指定されていればそれを採用。
@@ -56,48 +159,265 @@ fn main() -> Result<()> {
     };
 
     // -------------------------------------------------------------------------
-    // 3. 入力データの読み込み
+    // 3. 設定の読み込み
+    //    キー割り当て・プロンプト文言・取り込み先は設定ファイルから読む
+    //    （取り込み先は --clipboard、保持する秒数は --hold-timeout が優先）。
+    //    --from-clipboard では入力の読み込みにも取り込み先を使うため、
+    //    入力より先に決める。
+    // -------------------------------------------------------------------------
+    let mut config = Config::load(cli.config.as_deref())?;
+    config.clipboard.hold_timeout =
+        cli.hold_timeout.or(config.clipboard.hold_timeout);
+    config.clipboard.clear_on_exit |= cli.clear_on_exit;
+    config.clipboard.html |= cli.html;
+    config.clipboard.clear_after =
+        cli.clear_after.or(config.clipboard.clear_after);
+    config.clipboard.idle_timeout =
+        cli.idle_timeout.or(config.clipboard.idle_timeout);
+    if !CAN_WAIT_FOR_INPUT
+        && (config.clipboard.clear_after.is_some()
+            || config.clipboard.idle_timeout.is_some())
+    {
+        eprintln!(
+            "warning: この OS では、端末の入力を待っている間は --clear-after / --idle-timeout が働きません（--tui では働きます）"
+        );
+    }
+    let mut clipboard = build_clipboard(&cli, &config);
+
+    // -------------------------------------------------------------------------
+    // 4. 入力データの読み込み
     //    - ファイル指定時：ファイルから読み込み
+    //    - --from-clipboard：clipboard のテキストをそのまま使う
     //    - 標準入力：stdin から読み込み
     //
-    //    読み込んだバイト列は encoding.rs の detect_encoding_and_decode に渡し、
-    //    UTF-8 または Shift_JIS としてデコードする。
+    //    ファイル・標準入力から読んだバイト列は encoding.rs の
+    //    detect_encoding に渡し、判定したエンコードでデコードする。
+    //    clipboard のテキストはデコード済みなので渡さない。
     // -------------------------------------------------------------------------
     let (input_text, from_file, input_file_name) =
-        if let Some(path) = cli.input_file {
+        if let Some(ref path) = cli.input_file {
             // ファイル入力
-            let mut f = std::fs::File::open(&path)?;
+            let mut f = std::fs::File::open(path)?;
             let mut buf = Vec::new();
             use std::io::Read;
             f.read_to_end(&mut buf)?;
 
-            let (text, encoding_name) =
-                clip_frag::app::encoding::detect_encoding_and_decode(&buf)?;
-            eprintln!("encoding: {}", encoding_name);
+            let text = decode_input(&buf)?;
 
             (text, true, Some(path.to_string_lossy().to_string()))
+        } else if cli.from_clipboard {
+            // clipboard 入力
+            (read_from_clipboard(clipboard.as_mut())?, false, None)
         } else {
             // 標準入力
             let mut buf = Vec::new();
             use std::io::Read;
             std::io::stdin().read_to_end(&mut buf)?;
 
-            let (text, encoding_name) =
-                clip_frag::app::encoding::detect_encoding_and_decode(&buf)?;
-            eprintln!("encoding: {}", encoding_name);
+            let text = decode_input(&buf)?;
 
             (text, false, None)
         };
 
     // -------------------------------------------------------------------------
-    // 4. App の初期化
+    // 5. App の初期化
     //    App::new は CLI に依存しない純粋ロジック。
+    //    標準入力を入力データに使っていなければ、端末のない環境では
+    //    標準入力をコマンド列として読める。
     // -------------------------------------------------------------------------
+    let stdin_free = from_file || cli.from_clipboard;
+    let prompter = build_prompter(&cli, stdin_free)?;
+
     let mut app =
-        App::new(input_text, unit, max_unit, from_file, input_file_name)?;
+        App::new(input_text, unit, max_unit, from_file, input_file_name)?
+            .with_prompter(prompter)
+            .with_clipboard(clipboard)
+            .with_config(config);
 
     // -------------------------------------------------------------------------
-    // 5. 実行
+    // 6. 実行
+    //    --tui 指定時は全画面 TUI、それ以外は行指向のプロンプトで操作する。
+    //    セッションが終われば（Quit など）そのままプロセスを終える。
     // -------------------------------------------------------------------------
-    app.run()
+    #[cfg(feature = "tui")]
+    if cli.tui {
+        app.run_tui()?;
+        return Ok(());
+    }
+
+    app.run()?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// build_prompter
+// -----------------------------------------------------------------------------
+//
+// 操作の入力元を決める。
+//
+//   - --script: 与えたコマンド列（@FILE ならファイルの各行）
+//   - --yes N:  N 秒ごとに自動で進む
+//   - それ以外: 端末（TTY）
+//
+// 端末を開けない環境（cron・CI など）では、入力データをファイルや
+// clipboard から読んでいて標準入力が空いていれば（stdin_free）、
+// 標準入力を 1 行 1 コマンドのスクリプトとして読む。
+// それもできなければ使い方を案内して終了する。
+// -----------------------------------------------------------------------------
+fn build_prompter(cli: &Cli, stdin_free: bool) -> Result<Box<dyn Prompter>> {
+    if let Some(ref script) = cli.script {
+        let prompter = match script.strip_prefix('@') {
+            Some(path) => {
+                let text =
+                    std::fs::read_to_string(path).with_context(|| {
+                        format!("スクリプトを読み込めません: {}", path)
+                    })?;
+                ScriptPrompter::from_lines(&text)
+            }
+            None => ScriptPrompter::from_commands(script),
+        };
+        return Ok(Box::new(prompter));
+    }
+
+    if let Some(seconds) = cli.yes {
+        let interval = std::time::Duration::from_secs(seconds);
+        return Ok(Box::new(AutoAdvancePrompter::new(interval)));
+    }
+
+    if tty_available() {
+        return Ok(Box::new(TtyPrompter::new(cli.single_key)));
+    }
+
+    if stdin_free {
+        eprintln!(
+            "端末（{}）を開けないため、標準入力からコマンドを読みます。",
+            TTY_DEVICE
+        );
+        let mut text = String::new();
+        use std::io::Read;
+        std::io::stdin().read_to_string(&mut text)?;
+        return Ok(Box::new(ScriptPrompter::from_lines(&text)));
+    }
+
+    bail!(
+        "端末（{}）を開けません。端末のない環境では --script または --yes で実行してください",
+        TTY_DEVICE
+    )
+}
+
+// -----------------------------------------------------------------------------
+// clipboard_backend
+// -----------------------------------------------------------------------------
+//
+// 取り込み先を決める。--clipboard、設定ファイル、system の順に優先する。
+// -----------------------------------------------------------------------------
+fn clipboard_backend(cli: &Cli, config: &Config) -> ClipboardBackend {
+    cli.clipboard
+        .clone()
+        .or(config.clipboard.backend.clone())
+        .unwrap_or_default()
+}
+
+// -----------------------------------------------------------------------------
+// build_clipboard
+// -----------------------------------------------------------------------------
+//
+// 取り込み先を、書き込み先（--selection、設定ファイル、clipboard の順）を
+// 渡して作る。書き込み先を選べない取り込み先で clipboard 以外を選んだ
+// 場合は、無視されることを警告する。
+// -----------------------------------------------------------------------------
+fn build_clipboard(cli: &Cli, config: &Config) -> Box<dyn ClipboardSink> {
+    let backend = clipboard_backend(cli, config);
+    let selection =
+        cli.selection.or(config.clipboard.selection).unwrap_or_default();
+
+    if selection != Selection::Clipboard && !backend.honors_selection() {
+        eprintln!(
+            "warning: この取り込み先では --selection {} は働きません（system / wl-copy / xclip / xsel のみ）",
+            selection.as_str()
+        );
+    }
+
+    backend.build(selection)
+}
+
+// -----------------------------------------------------------------------------
+// decode_input
+// -----------------------------------------------------------------------------
+//
+// ファイル・標準入力から読んだバイト列をデコードし、判定したエンコードと
+// 確度を標準エラー出力に表示する。確度が低い場合は文字化けに注意を促す。
+// -----------------------------------------------------------------------------
+fn decode_input(buf: &[u8]) -> Result<String> {
+    let detection = detect_encoding(buf)?;
+
+    eprintln!(
+        "encoding: {}（{}確度 {:.0}%）",
+        detection.encoding,
+        if detection.bom { "BOM、" } else { "" },
+        detection.confidence * 100.0
+    );
+    if detection.confidence < LOW_CONFIDENCE {
+        eprintln!(
+            "warning: エンコードの判定に自信がありません。文字化けしていないか確認してください"
+        );
+    }
+
+    Ok(detection.text)
+}
+
+// -----------------------------------------------------------------------------
+// read_from_clipboard
+// -----------------------------------------------------------------------------
+//
+// --from-clipboard の入力。取り込み先（--clipboard）から現在の内容を読む。
+// 読めない取り込み先（外部コマンド・osc52・remote:）や、clipboard が空の
+// 場合はエラーにする。
+// -----------------------------------------------------------------------------
+fn read_from_clipboard(clipboard: &mut dyn ClipboardSink) -> Result<String> {
+    if !clipboard.can_read() {
+        bail!("この取り込み先からは clipboard を読み込めません（--from-clipboard は system / file: のみ）");
+    }
+
+    let text = clipboard
+        .get_text()
+        .context("clipboard を読み込めません")?
+        .context("clipboard が空か、テキストではありません")?;
+    eprintln!("input: clipboard");
+
+    Ok(text)
+}
+
+// -----------------------------------------------------------------------------
+// run_serve
+// -----------------------------------------------------------------------------
+//
+// `clip_frag serve` を実行する。
+// トークンは環境変数 CLIP_FRAG_TOKEN から読み、未設定なら作って表示する。
+// 受け取ったフラグメントは、--clipboard / 設定ファイルの取り込み先に設定する。
+// -----------------------------------------------------------------------------
+fn run_serve(cli: &Cli, listen: &Endpoint) -> Result<()> {
+    let config = Config::load(cli.config.as_deref())?;
+    let mut sink = build_clipboard(cli, &config);
+
+    let token = match std::env::var(TOKEN_ENV) {
+        Ok(token) if !token.is_empty() => token,
+        _ => {
+            let token = generate_token()?;
+            eprintln!("token: {}", token);
+            eprintln!(
+                "リモート側で環境変数 {} にこの値を設定してください。",
+                TOKEN_ENV
+            );
+            token
+        }
+    };
+
+    let listener = Listener::bind(listen)?;
+    eprintln!(
+        "{} で待ち受けています（Ctrl-C で終了）。",
+        listener.local_endpoint()?
+    );
+
+    serve(&listener, &token, sink.as_mut())
 }
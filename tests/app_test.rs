@@ -0,0 +1,463 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use clip_frag::app::clipboard::VERIFY_ATTEMPTS;
+use clip_frag::app::config::Config;
+use clip_frag::app::{
+    App, AutoAdvancePrompter, ClipboardSink, Outcome, Prompter,
+    ScriptPrompter, Unit,
+};
+
+// -----------------------------------------------------------------------------
+// ScriptedPrompter
+// -----------------------------------------------------------------------------
+//
+// あらかじめ用意した入力を順に返す Prompter。
+// 入力を使い切ったらエラーにする（ループが終わらない場合の保険）。
+// 次の入力が IDLE なら、wait_for_input は timeout だけ待って false を返す
+// （入力がないまま時間が過ぎたことにする）。
+// -----------------------------------------------------------------------------
+struct ScriptedPrompter {
+    inputs: VecDeque<String>,
+}
+
+impl ScriptedPrompter {
+    fn new(inputs: &[&str]) -> Self {
+        Self { inputs: inputs.iter().map(|s| s.to_string()).collect() }
+    }
+
+    fn next(&mut self) -> Result<String> {
+        match self.inputs.pop_front() {
+            Some(input) => Ok(input),
+            None => bail!("script exhausted"),
+        }
+    }
+}
+
+const IDLE: &str = "<idle>";
+
+impl Prompter for ScriptedPrompter {
+    fn read_choice(&mut self, _line_starters: &[char]) -> Result<String> {
+        self.next()
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        self.next()
+    }
+
+    fn wait_for_input(&mut self, timeout: Duration) -> Result<bool> {
+        if self.inputs.front().is_some_and(|input| input == IDLE) {
+            std::thread::sleep(timeout);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// MemoryClipboard
+// -----------------------------------------------------------------------------
+//
+// 書き込まれた内容を順に記録する ClipboardSink。クリアは "" として、
+// 終了後に残す内容（persist）は "keep:" を付けて記録する。
+// 読み出し（get_text）では最後に記録した内容を返す。
+// corrupt が 0 でなければ、その回数だけ書き込みを途中で切る。
+// HTML で書き込まれた場合は、代替テキストを log に、HTML を html に記録する。
+// -----------------------------------------------------------------------------
+#[derive(Clone, Default)]
+struct MemoryClipboard {
+    log: Rc<RefCell<Vec<String>>>,
+    html: Rc<RefCell<Vec<String>>>,
+    corrupt: Rc<Cell<usize>>,
+}
+
+impl ClipboardSink for MemoryClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        let text = if self.corrupt.get() > 0 {
+            self.corrupt.set(self.corrupt.get() - 1);
+            &text[..text.len() / 2]
+        } else {
+            text
+        };
+        self.log.borrow_mut().push(text.to_string());
+        Ok(())
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<()> {
+        self.html.borrow_mut().push(html.to_string());
+        self.set_text(alt_text)
+    }
+
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.log.borrow_mut().push(String::new());
+        Ok(())
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>> {
+        Ok(self.log.borrow().last().filter(|t| !t.is_empty()).cloned())
+    }
+
+    fn persist(&mut self, text: &str, _timeout: Duration) -> Result<()> {
+        self.log.borrow_mut().push(format!("keep:{}", text));
+        Ok(())
+    }
+}
+
+/// 1 行 4 文字（改行込み）の 4 行。最大 8 文字なら 2 行ずつに分割される
+const INPUT: &str = "aaa\nbbb\nccc\nddd\n";
+
+/// INPUT を最大 8 文字（2 行ずつ）で分割する
+fn run_session(
+    from_file: bool,
+    inputs: &[&str],
+) -> (Result<Outcome>, Vec<String>) {
+    run_session_with(&[], Config::default(), from_file, inputs)
+}
+
+/// clipboard の初期内容と設定を指定して run_session する
+fn run_session_with(
+    initial: &[&str],
+    config: Config,
+    from_file: bool,
+    inputs: &[&str],
+) -> (Result<Outcome>, Vec<String>) {
+    let clipboard = MemoryClipboard::default();
+    clipboard.log.borrow_mut().extend(initial.iter().map(|s| s.to_string()));
+    let name = from_file.then_some("a.txt");
+
+    let (outcome, _) = run_app(
+        INPUT,
+        name,
+        config,
+        Box::new(ScriptedPrompter::new(inputs)),
+        &clipboard,
+    );
+    let log = clipboard.log.borrow().clone();
+    (outcome, log)
+}
+
+/// text を最大 8 文字で分割し、prompter の入力で clipboard に取り込む
+/// （file_name を指定するとファイル入力として扱う）
+fn run_app(
+    text: &str,
+    file_name: Option<&str>,
+    config: Config,
+    prompter: Box<dyn Prompter>,
+    clipboard: &MemoryClipboard,
+) -> (Result<Outcome>, App) {
+    let mut app = App::new(
+        text.into(),
+        Unit::Chars,
+        8,
+        file_name.is_some(),
+        file_name.map(str::to_string),
+    )
+    .unwrap()
+    .with_prompter(prompter)
+    .with_clipboard(Box::new(clipboard.clone()))
+    .with_config(config);
+
+    (app.run(), app)
+}
+
+#[test]
+fn test_session_copies_fragments_and_clears_on_quit() {
+    let (outcome, log) = run_session(false, &["y", "", ""]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["aaa\nbbb\n", "ccc\nddd\n", ""]);
+}
+
+#[test]
+fn test_session_with_file_header_and_footer() {
+    let (outcome, log) = run_session(true, &["y", "y", "y", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(
+        log,
+        vec![
+            "以下に、ファイル: a.txt を入力します。\n---\n",
+            "aaa\nbbb\n",
+            "ccc\nddd\n",
+            "以上が、ファイル: a.txt の内容である。\n",
+            "",
+        ]
+    );
+}
+
+#[test]
+fn test_session_restores_original_clipboard_on_quit() {
+    let (outcome, log) =
+        run_session_with(&["orig"], Config::default(), false, &["y", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    // 戻した内容は、終了後も保持プロセスに引き継ぐ
+    assert_eq!(log, vec!["orig", "aaa\nbbb\n", "orig", "keep:orig"]);
+}
+
+#[test]
+fn test_session_clears_when_original_cannot_be_restored() {
+    let clipboard = MemoryClipboard::default();
+    clipboard.log.borrow_mut().push("orig".into());
+    // 起動前の内容に戻す書き込みが VERIFY_ATTEMPTS 回とも途中で切れる
+    clipboard.corrupt.set(VERIFY_ATTEMPTS as usize);
+
+    let (outcome, _) = run_app(
+        INPUT,
+        None,
+        Config::default(),
+        Box::new(ScriptedPrompter::new(&["q"])),
+        &clipboard,
+    );
+
+    // エラーにせず、クリアして Quit で終わる
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(*clipboard.log.borrow(), vec!["orig", "or", "or", "or", ""]);
+}
+
+#[test]
+fn test_session_clear_on_exit_ignores_original_clipboard() {
+    let mut config = Config::default();
+    config.clipboard.clear_on_exit = true;
+
+    let (outcome, log) = run_session_with(&["orig"], config, false, &["q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["orig", ""]);
+}
+
+#[test]
+fn test_session_retries_and_stays_on_mismatched_fragment() {
+    let clipboard = MemoryClipboard::default();
+    // 最初の取り込みは 3 回とも失敗し、2 回目の取り込みで成功する
+    clipboard.corrupt.set(3);
+
+    let (outcome, app) = run_app(
+        INPUT,
+        None,
+        Config::default(),
+        Box::new(ScriptedPrompter::new(&["y", "y", "q"])),
+        &clipboard,
+    );
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(
+        *clipboard.log.borrow(),
+        vec!["aaa\n", "aaa\n", "aaa\n", "aaa\nbbb\n", ""]
+    );
+    assert_eq!(app.state.history, vec![0..2]);
+}
+
+#[test]
+fn test_session_clears_clipboard_after_timeout() {
+    let mut config = Config::default();
+    config.clipboard.clear_after = Some(0);
+
+    let (outcome, log) = run_session_with(&[], config, false, &["y", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["aaa\nbbb\n", "", ""]);
+}
+
+#[test]
+fn test_session_keep_after_clear_after_does_not_persist() {
+    let mut config = Config::default();
+    config.clipboard.clear_after = Some(0);
+
+    let (outcome, log) =
+        run_session_with(&[], config, false, &["y", "y", "k"]);
+
+    // クリアした内容を保持プロセスに渡し直さない
+    assert_eq!(outcome.unwrap(), Outcome::Keep);
+    assert_eq!(log, vec!["aaa\nbbb\n", "", "ccc\nddd\n", ""]);
+}
+
+#[test]
+fn test_session_ends_like_quit_after_idle_timeout() {
+    let mut config = Config::default();
+    config.clipboard.idle_timeout = Some(0);
+
+    // 入力を読む前に時間切れになるので、スクリプトは使われない
+    let (outcome, log) = run_session_with(&["orig"], config, false, &[]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["orig", "orig", "keep:orig"]);
+}
+
+#[test]
+fn test_session_idle_timeout_while_reading_fragment_number() {
+    let mut config = Config::default();
+    config.clipboard.idle_timeout = Some(1);
+
+    // 検索後の番号入力で時間切れになっても Quit で終わる
+    // （IDLE は読まれずに残る）
+    let (outcome, log) =
+        run_session_with(&["orig"], config, false, &["/ccc", IDLE]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["orig", "orig", "keep:orig"]);
+}
+
+/// --html のテスト用の、1 フラグメント（8 文字以内）に収まる Rust のコード
+const RUST_INPUT: &str = "fn a(){}";
+
+#[test]
+fn test_session_copies_fragments_as_html() {
+    let clipboard = MemoryClipboard::default();
+    let mut config = Config::default();
+    config.clipboard.html = true;
+
+    let (outcome, _) = run_app(
+        RUST_INPUT,
+        Some("lib.rs"),
+        config,
+        Box::new(ScriptedPrompter::new(&["y", "s", "q"])),
+        &clipboard,
+    );
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+
+    // ヘッダ・フッタはテキストのまま、フラグメントだけを HTML にする
+    assert_eq!(clipboard.log.borrow()[1], RUST_INPUT);
+    let html = clipboard.html.borrow();
+    assert_eq!(html.len(), 1);
+    assert!(html[0].contains("language-rust"));
+    assert!(html[0].contains(">fn</span>"));
+}
+
+#[test]
+fn test_session_html_prev_keeps_header_and_footer_as_text() {
+    let clipboard = MemoryClipboard::default();
+    let mut config = Config::default();
+    config.clipboard.html = true;
+
+    let (outcome, _) = run_app(
+        RUST_INPUT,
+        Some("lib.rs"),
+        config,
+        Box::new(ScriptedPrompter::new(&["p", "y", "y", "p", "q"])),
+        &clipboard,
+    );
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+
+    // P(rev) で取り込み直したヘッダ・フッタも HTML にしない
+    let header = "以下に、ファイル: lib.rs を入力します。\n---\n";
+    let footer = "以上が、ファイル: lib.rs の内容である。\n";
+    let log = clipboard.log.borrow();
+    assert_eq!(log[..5], [header, header, RUST_INPUT, footer, footer]);
+    assert_eq!(clipboard.html.borrow().len(), 1);
+}
+
+#[test]
+fn test_session_keep_leaves_clipboard_without_clearing() {
+    let (outcome, log) = run_session(false, &["y", "", "k"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Keep);
+    assert_eq!(log, vec!["aaa\nbbb\n", "ccc\nddd\n", "keep:ccc\nddd\n"]);
+}
+
+#[test]
+fn test_session_keep_is_only_accepted_at_exit() {
+    let (outcome, log) = run_session(false, &["k", "y", "", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["aaa\nbbb\n", "ccc\nddd\n", ""]);
+}
+
+#[test]
+fn test_session_skip_and_prev() {
+    let (outcome, log) = run_session(false, &["y", "p", "s", "p", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["aaa\nbbb\n", "aaa\nbbb\n", "aaa\nbbb\n", ""]);
+}
+
+#[test]
+fn test_session_quit_in_main_loop() {
+    let (outcome, log) = run_session(false, &["x", "?", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec![""]);
+}
+
+#[test]
+fn test_session_rewinds_from_exit_loop() {
+    // 最後まで取り込んだ後に 1 番目へ戻ると、2 番目から main_loop をやり直す
+    let (outcome, log) = run_session(false, &["y", "y", "1", "s", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["aaa\nbbb\n", "ccc\nddd\n", "aaa\nbbb\n", ""]);
+}
+
+#[test]
+fn test_session_search_and_jump() {
+    let (outcome, log) = run_session(false, &["/ddd", "2", "q"]);
+
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(log, vec!["ccc\nddd\n", ""]);
+}
+
+#[test]
+fn test_session_propagates_prompter_error() {
+    let (outcome, _) = run_session(false, &["y"]);
+
+    assert!(outcome.is_err());
+}
+
+#[test]
+fn test_session_with_script_prompter() {
+    let clipboard = MemoryClipboard::default();
+    let (outcome, _) = run_app(
+        INPUT,
+        None,
+        Config::default(),
+        Box::new(ScriptPrompter::from_commands("s,y")),
+        &clipboard,
+    );
+
+    // スクリプトを使い切ると Quit で終わる
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(*clipboard.log.borrow(), vec!["ccc\nddd\n", ""]);
+}
+
+#[test]
+fn test_session_with_script_prompter_and_rebound_quit() {
+    let clipboard = MemoryClipboard::default();
+    let config = Config::from_toml("[keys]\nquit = [\"x\"]\n").unwrap();
+
+    let (outcome, _) = run_app(
+        INPUT,
+        None,
+        config,
+        Box::new(ScriptPrompter::from_commands("y")),
+        &clipboard,
+    );
+
+    // quit のキーを変えていても、スクリプトを使い切れば Quit で終わる
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(*clipboard.log.borrow(), vec!["aaa\nbbb\n", ""]);
+}
+
+#[test]
+fn test_session_with_auto_advance() {
+    let clipboard = MemoryClipboard::default();
+    let (outcome, _) = run_app(
+        INPUT,
+        None,
+        Config::default(),
+        Box::new(AutoAdvancePrompter::new(Duration::ZERO)),
+        &clipboard,
+    );
+
+    // すべて取り込んだ後、exit_loop のデフォルト（Quit）で終わる
+    assert_eq!(outcome.unwrap(), Outcome::Quit);
+    assert_eq!(*clipboard.log.borrow(), vec!["aaa\nbbb\n", "ccc\nddd\n", ""]);
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+// -----------------------------------------------------------------------------
+// run_from_clipboard
+// -----------------------------------------------------------------------------
+//
+// clip_frag を --from-clipboard で実行する。取り込み先は backend
+// （clipboard の内容を書いたファイルなら file:PATH）。
+// 利用者の設定ファイルを読まないよう、空の設定ファイルを渡す。
+// -----------------------------------------------------------------------------
+fn run_from_clipboard(name: &str, backend: &str, args: &[&str]) -> Output {
+    let config = temp_path(&format!("{}.toml", name));
+    std::fs::write(&config, "").unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_clip_frag"))
+        .arg("--from-clipboard")
+        .arg("--clipboard")
+        .arg(backend)
+        .arg("--config")
+        .arg(&config)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "clip_frag_cli_test-{}-{}",
+        std::process::id(),
+        name
+    ))
+}
+
+#[test]
+fn test_from_clipboard_splits_clipboard_contents() {
+    let clipboard = temp_path("split.txt");
+    std::fs::write(&clipboard, "aaaa\nbbbb\ncccc\n").unwrap();
+    let backend = format!("file:{}", clipboard.display());
+
+    let output = run_from_clipboard(
+        "split",
+        &backend,
+        &["-c", "5", "--script", "y,y,y,k"],
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("input: clipboard"));
+    assert!(stderr.contains("[1/3]"));
+    assert!(stderr.contains("[3/3]"));
+    // K(eep) で終了したので、最後のフラグメントが残る
+    assert_eq!(std::fs::read_to_string(&clipboard).unwrap(), "cccc\n");
+}
+
+#[test]
+fn test_from_clipboard_empty_is_error() {
+    let clipboard = temp_path("empty.txt");
+    std::fs::write(&clipboard, "").unwrap();
+    let backend = format!("file:{}", clipboard.display());
+
+    let output = run_from_clipboard("empty", &backend, &["--script", "q"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("clipboard が空か、テキストではありません"));
+}
+
+#[test]
+fn test_from_clipboard_unreadable_backend_is_error() {
+    let output =
+        run_from_clipboard("unreadable", "command:cat", &["--script", "q"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("この取り込み先からは clipboard を読み込めません"));
+}
@@ -0,0 +1,241 @@
+use std::path::PathBuf;
+
+use clip_frag::app::clipboard::{
+    base64_encode, content_hash, osc52_sequence, preset_commands,
+    set_text_verified, ClipboardBackend, ClipboardSink, CommandClipboard,
+    ContentMismatch, FileClipboard, Multiplexer, Selection, VERIFY_ATTEMPTS,
+};
+use clip_frag::app::config::Config;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "clip_frag_{}_{}",
+        name,
+        std::process::id()
+    ))
+}
+
+#[test]
+fn test_parse_backend() {
+    assert_eq!(
+        "system".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::System
+    );
+    assert_eq!(
+        "xclip".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::Preset("xclip".into())
+    );
+    assert_eq!(
+        "command: my-copy --flag".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::Command("my-copy --flag".into())
+    );
+    assert_eq!(
+        "file:/tmp/out.txt".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::File("/tmp/out.txt".into())
+    );
+
+    assert!("clippy".parse::<ClipboardBackend>().is_err());
+    assert!("command:".parse::<ClipboardBackend>().is_err());
+    assert!("file:".parse::<ClipboardBackend>().is_err());
+}
+
+#[test]
+fn test_backend_from_config() {
+    let config =
+        Config::from_toml("[clipboard]\nbackend = \"pbcopy\"\n").unwrap();
+    assert_eq!(
+        config.clipboard.backend,
+        Some(ClipboardBackend::Preset("pbcopy".into()))
+    );
+
+    assert!(Config::from_toml("[clipboard]\nbackend = \"nope\"\n").is_err());
+}
+
+#[test]
+fn test_file_clipboard() {
+    let path = temp_path("file_clipboard");
+    let mut clipboard = FileClipboard::new(&path);
+    assert_eq!(clipboard.get_text().unwrap(), None);
+
+    clipboard.set_text("fragment\n").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "fragment\n");
+    assert_eq!(clipboard.get_text().unwrap(), Some("fragment\n".into()));
+
+    clipboard.clear().unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    assert_eq!(clipboard.get_text().unwrap(), None);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 最初の fail 回だけ書き込みを捨てる ClipboardSink
+struct FlakyClipboard {
+    fail: usize,
+    writes: usize,
+    content: Option<String>,
+}
+
+impl ClipboardSink for FlakyClipboard {
+    fn set_text(&mut self, text: &str) -> anyhow::Result<()> {
+        self.writes += 1;
+        if self.writes > self.fail {
+            self.content = Some(text.to_string());
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) -> anyhow::Result<()> {
+        self.content = None;
+        Ok(())
+    }
+
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn get_text(&mut self) -> anyhow::Result<Option<String>> {
+        Ok(self.content.clone())
+    }
+}
+
+#[test]
+fn test_set_text_verified_retries_until_match() {
+    let mut clipboard = FlakyClipboard { fail: 2, writes: 0, content: None };
+
+    set_text_verified(&mut clipboard, "fragment").unwrap();
+    assert_eq!(clipboard.writes, 3);
+    assert_eq!(clipboard.content.as_deref(), Some("fragment"));
+}
+
+#[test]
+fn test_set_text_verified_reports_mismatch() {
+    let mut clipboard =
+        FlakyClipboard { fail: usize::MAX, writes: 0, content: None };
+
+    let err = set_text_verified(&mut clipboard, "fragment").unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContentMismatch>(),
+        Some(&ContentMismatch { expected_bytes: 8, actual_bytes: 0 })
+    );
+    assert_eq!(clipboard.writes, VERIFY_ATTEMPTS as usize);
+}
+
+#[test]
+fn test_content_hash() {
+    assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(content_hash("a"), content_hash("a"));
+    assert_ne!(content_hash("ab"), content_hash("ba"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_clipboard() {
+    let path = temp_path("command_clipboard");
+    let mut clipboard =
+        CommandClipboard::new(format!("cat > '{}'", path.display()));
+
+    clipboard.set_text("あいう\n").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "あいう\n");
+
+    clipboard.clear().unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+    std::fs::remove_file(&path).unwrap();
+
+    // コマンドが失敗した場合はエラーになる
+    assert!(CommandClipboard::new("false").set_text("x").is_err());
+}
+
+#[test]
+fn test_base64_encode() {
+    assert_eq!(base64_encode(b""), "");
+    assert_eq!(base64_encode(b"f"), "Zg==");
+    assert_eq!(base64_encode(b"fo"), "Zm8=");
+    assert_eq!(base64_encode(b"foo"), "Zm9v");
+    assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    assert_eq!(base64_encode("あ".as_bytes()), "44GC");
+}
+
+#[test]
+fn test_osc52_sequence() {
+    assert_eq!(
+        osc52_sequence("Zm9v", Multiplexer::None),
+        "\x1b]52;c;Zm9v\x07"
+    );
+    assert_eq!(
+        osc52_sequence("Zm9v", Multiplexer::Tmux),
+        "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+    );
+
+    // screen では短く区切った DCS を連ねる
+    let payload = "A".repeat(200);
+    let screen = osc52_sequence(&payload, Multiplexer::Screen);
+    assert!(screen.starts_with("\x1bP\x1b]52;c;"));
+    assert_eq!(screen.matches("\x1bP").count(), 3);
+    assert_eq!(
+        screen.replace("\x1bP", "").replace("\x1b\\", ""),
+        osc52_sequence(&payload, Multiplexer::None)
+    );
+
+    assert_eq!(
+        "osc52".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::Osc52
+    );
+}
+
+#[test]
+fn test_parse_selection() {
+    assert_eq!(
+        "clipboard".parse::<Selection>().unwrap(),
+        Selection::Clipboard
+    );
+    assert_eq!("primary".parse::<Selection>().unwrap(), Selection::Primary);
+    assert_eq!("both".parse::<Selection>().unwrap(), Selection::Both);
+    assert!("secondary".parse::<Selection>().is_err());
+
+    assert!(Selection::Both.includes_clipboard());
+    assert!(Selection::Both.includes_primary());
+    assert!(!Selection::Primary.includes_clipboard());
+    assert!(!Selection::Clipboard.includes_primary());
+
+    let config =
+        Config::from_toml("[clipboard]\nselection = \"primary\"\n").unwrap();
+    assert_eq!(config.clipboard.selection, Some(Selection::Primary));
+}
+
+#[test]
+fn test_preset_commands_follow_selection() {
+    assert_eq!(
+        preset_commands("xclip", Selection::Clipboard),
+        vec!["xclip -selection clipboard"]
+    );
+    assert_eq!(
+        preset_commands("wl-copy", Selection::Primary),
+        vec!["wl-copy --primary"]
+    );
+    assert_eq!(
+        preset_commands("xsel", Selection::Both),
+        vec!["xsel --clipboard --input", "xsel --primary --input"]
+    );
+    // PRIMARY のないプリセットは selection に関係なく同じ
+    assert_eq!(preset_commands("pbcopy", Selection::Primary), vec!["pbcopy"]);
+
+    assert!(ClipboardBackend::System.honors_selection());
+    assert!(ClipboardBackend::Preset("xclip".into()).honors_selection());
+    assert!(!ClipboardBackend::Preset("tmux".into()).honors_selection());
+    assert!(!ClipboardBackend::Command("my-copy".into()).honors_selection());
+}
+
+#[test]
+fn test_command_clipboard_runs_every_command() {
+    let (a, b) = (temp_path("both-a.txt"), temp_path("both-b.txt"));
+    let mut clipboard = CommandClipboard::with_commands(vec![
+        format!("cat > '{}'", a.display()),
+        format!("cat > '{}'", b.display()),
+    ]);
+
+    clipboard.set_text("hello\n").unwrap();
+
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "hello\n");
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "hello\n");
+}
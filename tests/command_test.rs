@@ -0,0 +1,153 @@
+use clip_frag::app::command::{
+    parse_command, parse_command_with, Command, KeyMap, TuiAction, TuiKeyMap,
+};
+use clip_frag::app::state::Unit;
+
+#[test]
+fn test_parse_basic_commands() {
+    assert_eq!(parse_command("y"), Some(Command::Yes));
+    assert_eq!(parse_command("YES"), Some(Command::Yes));
+    assert_eq!(parse_command("p"), Some(Command::Prev));
+    assert_eq!(parse_command("Quit"), Some(Command::Quit));
+    assert_eq!(parse_command("k"), Some(Command::Keep));
+    assert_eq!(parse_command("?"), Some(Command::Help));
+}
+
+#[test]
+fn test_parse_navigation_commands() {
+    assert_eq!(parse_command("b"), Some(Command::Back(1)));
+    assert_eq!(parse_command("b 3"), Some(Command::Back(3)));
+    assert_eq!(parse_command("back2"), Some(Command::Back(2)));
+    assert_eq!(parse_command("f"), Some(Command::Forward(1)));
+    assert_eq!(parse_command("forward 4"), Some(Command::Forward(4)));
+    assert_eq!(parse_command("g 5"), Some(Command::Goto(5)));
+    assert_eq!(parse_command("7"), Some(Command::Goto(7)));
+    assert_eq!(parse_command("first"), Some(Command::First));
+    assert_eq!(parse_command(">"), Some(Command::Last));
+}
+
+#[test]
+fn test_parse_invalid_commands() {
+    assert_eq!(parse_command("x"), None);
+    assert_eq!(parse_command("g"), None);
+    assert_eq!(parse_command("b x"), None);
+    assert_eq!(parse_command("y 2"), None);
+}
+
+#[test]
+fn test_parse_resize_command() {
+    assert_eq!(
+        parse_command("m 20000"),
+        Some(Command::Resize { max_unit: Some(20000), unit: None })
+    );
+    assert_eq!(
+        parse_command("max 8_000 bytes"),
+        Some(Command::Resize {
+            max_unit: Some(8000),
+            unit: Some(Unit::Bytes)
+        })
+    );
+    assert_eq!(
+        parse_command("m c"),
+        Some(Command::Resize { max_unit: None, unit: Some(Unit::Chars) })
+    );
+    assert_eq!(parse_command("m"), None);
+    assert_eq!(parse_command("m 0"), None);
+}
+
+#[test]
+fn test_parse_boundary_commands() {
+    assert_eq!(parse_command("+"), Some(Command::Grow(1)));
+    assert_eq!(parse_command("+3"), Some(Command::Grow(3)));
+    assert_eq!(parse_command("- 2"), Some(Command::Shrink(2)));
+    assert_eq!(parse_command("]"), Some(Command::NextBlank));
+    assert_eq!(parse_command("["), Some(Command::PrevBlank));
+}
+
+#[test]
+fn test_parse_search_command() {
+    assert_eq!(
+        parse_command("/fn Finalize"),
+        Some(Command::Search("fn Finalize".to_string()))
+    );
+    assert_eq!(parse_command("/"), None);
+}
+
+#[test]
+fn test_parse_with_custom_keymap() {
+    let keys = KeyMap {
+        yes: vec!["n".into(), "space".into()],
+        goto: vec!["j".into()],
+        ..KeyMap::default()
+    };
+
+    assert_eq!(parse_command_with("n", &keys), Some(Command::Yes));
+    assert_eq!(parse_command_with("space", &keys), Some(Command::Yes));
+    assert_eq!(parse_command_with("y", &keys), None);
+    assert_eq!(parse_command_with("j 4", &keys), Some(Command::Goto(4)));
+    assert_eq!(parse_command_with("g 4", &keys), None);
+    assert_eq!(parse_command_with("left", &keys), Some(Command::Back(1)));
+}
+
+#[test]
+fn test_keymap_line_starters_and_help() {
+    let keys = KeyMap::default();
+    assert_eq!(keys.line_starters(), vec!['/', 'g', 'm']);
+
+    let help = keys.help_text();
+    assert!(help.contains("y, yes, space"));
+    assert!(help.contains("g, goto K / K"));
+}
+
+#[test]
+fn test_parse_with_custom_search_key() {
+    let keys =
+        KeyMap { search: vec!["f".into(), "//".into()], ..KeyMap::default() };
+
+    assert_eq!(
+        parse_command_with("fFoo", &keys),
+        Some(Command::Search("Foo".to_string()))
+    );
+    // 長い方の search キーを採用する
+    assert_eq!(
+        parse_command_with("//bar", &keys),
+        Some(Command::Search("bar".to_string()))
+    );
+    assert_eq!(parse_command_with("/bar", &keys), None);
+    assert_eq!(keys.line_starters(), vec!['f', 'g', 'm']);
+    assert!(keys.help_text().contains("fPATTERN, //PATTERN"));
+}
+
+#[test]
+fn test_keymap_validate() {
+    assert!(KeyMap::default().validate().is_ok());
+
+    // 大文字・小文字を区別せずに重複を見つける
+    let keys = KeyMap { quit: vec!["Y".into()], ..KeyMap::default() };
+    let err = keys.validate().unwrap_err().to_string();
+    assert!(err.contains("\"y\" が yes と quit の両方"), "{}", err);
+
+    let keys = KeyMap { search: vec!["s".into()], ..KeyMap::default() };
+    assert!(keys.validate().is_err());
+
+    let keys = KeyMap { help: vec![String::new()], ..KeyMap::default() };
+    assert!(keys.validate().is_err());
+}
+
+#[test]
+fn test_tui_keymap() {
+    let keys = TuiKeyMap::default();
+    assert!(keys.validate().is_ok());
+    assert_eq!(keys.action("g"), Some(TuiAction::First));
+    assert_eq!(keys.action("G"), Some(TuiAction::Last));
+    assert_eq!(keys.action("enter"), Some(TuiAction::Copy));
+    assert_eq!(keys.action("x"), None);
+
+    assert!(keys.help_line().starts_with("enter/c/y:copy  s:skip  "));
+
+    let keys = TuiKeyMap { skip: vec!["x".into()], ..TuiKeyMap::default() };
+    assert!(keys.help_line().contains("  x:skip  "));
+
+    let keys = TuiKeyMap { skip: vec!["j".into()], ..TuiKeyMap::default() };
+    assert!(keys.validate().is_err());
+}
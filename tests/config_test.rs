@@ -0,0 +1,65 @@
+use std::io::Write;
+
+use clip_frag::app::command::KeyMap;
+use clip_frag::app::config::{render_template, Config, Prompts};
+
+#[test]
+fn test_config_from_toml_partial() {
+    let config = Config::from_toml(
+        r#"
+        [keys]
+        yes = ["n", "space"]
+
+        [prompts]
+        exit = "p/q > "
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.keys.yes, vec!["n", "space"]);
+    // 指定しなかった項目は既定値のまま
+    assert_eq!(config.keys.quit, KeyMap::default().quit);
+    assert_eq!(config.prompts.exit, "p/q > ");
+    assert_eq!(config.prompts.main, Prompts::default().main);
+}
+
+#[test]
+fn test_config_rejects_unknown_keys() {
+    assert!(Config::from_toml("[keys]\nyess = [\"n\"]\n").is_err());
+}
+
+#[test]
+fn test_config_rejects_duplicate_keys() {
+    let err = Config::from_toml("[keys]\nquit = [\"y\"]\n").unwrap_err();
+    assert!(err.to_string().contains("yes と quit"), "{}", err);
+
+    let err = Config::from_toml("[keys.tui]\nquit = [\"j\"]\n").unwrap_err();
+    assert!(err.to_string().contains("[keys.tui]"), "{}", err);
+
+    let config = Config::from_toml("[keys.tui]\ndown = [\"down\"]\n").unwrap();
+    assert_eq!(config.keys.tui.down, vec!["down"]);
+}
+
+#[test]
+fn test_config_load() {
+    let path = std::env::temp_dir()
+        .join(format!("clip_frag_config_test_{}.toml", std::process::id()));
+    let mut f = std::fs::File::create(&path).unwrap();
+    writeln!(f, "[keys]\nquit = [\"x\"]").unwrap();
+    drop(f);
+
+    let config = Config::load(Some(&path)).unwrap();
+    assert_eq!(config.keys.quit, vec!["x"]);
+
+    std::fs::remove_file(&path).unwrap();
+
+    // 明示したファイルがなければエラー
+    assert!(Config::load(Some(&path)).is_err());
+}
+
+#[test]
+fn test_render_template() {
+    let vars = [("index", "3".to_string()), ("count", "12".to_string())];
+
+    assert_eq!(render_template("[{index}/{count}] {x}", &vars), "[3/12] {x}");
+}
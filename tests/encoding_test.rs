@@ -1,4 +1,5 @@
-use clip_frag::app::encoding::detect_encoding_and_decode;
+use clip_frag::app::encoding::{detect_encoding, detect_encoding_and_decode};
+use encoding_rs::{Encoding, EUC_JP, ISO_2022_JP, SHIFT_JIS};
 
 #[test]
 fn test_detect_utf8() {
@@ -32,3 +33,131 @@ fn test_invalid_encoding() {
 
     assert!(result.is_err());
 }
+
+// -----------------------------------------------------------------------------
+// detect_encoding（BOM・EUC-JP・ISO-2022-JP・UTF-16）
+// -----------------------------------------------------------------------------
+
+const SAMPLE: &str = "日本語のテキストです。\nこれは二行目です。\n";
+
+fn encode(encoding: &'static Encoding, text: &str) -> Vec<u8> {
+    let (bytes, _, had_errors) = encoding.encode(text);
+    assert!(!had_errors);
+    bytes.into_owned()
+}
+
+fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+    text.encode_utf16()
+        .flat_map(
+            |u| {
+                if big_endian {
+                    u.to_be_bytes()
+                } else {
+                    u.to_le_bytes()
+                }
+            },
+        )
+        .collect()
+}
+
+#[test]
+fn test_detect_ascii_is_utf8_with_full_confidence() {
+    let detection = detect_encoding(b"fn main() {}\n").unwrap();
+
+    assert_eq!(detection.encoding, "UTF-8");
+    assert_eq!(detection.confidence, 1.0);
+    assert!(!detection.bom);
+}
+
+#[test]
+fn test_detect_utf8_bom_is_stripped() {
+    let mut data = vec![0xEF, 0xBB, 0xBF];
+    data.extend_from_slice(SAMPLE.as_bytes());
+
+    let detection = detect_encoding(&data).unwrap();
+
+    assert_eq!(detection.text, SAMPLE);
+    assert_eq!(detection.encoding, "UTF-8");
+    assert_eq!(detection.confidence, 1.0);
+    assert!(detection.bom);
+}
+
+#[test]
+fn test_detect_utf16_with_bom() {
+    let mut le = vec![0xFF, 0xFE];
+    le.extend(utf16(SAMPLE, false));
+    let mut be = vec![0xFE, 0xFF];
+    be.extend(utf16(SAMPLE, true));
+
+    let le = detect_encoding(&le).unwrap();
+    let be = detect_encoding(&be).unwrap();
+
+    assert_eq!((le.text.as_str(), le.encoding), (SAMPLE, "UTF-16LE"));
+    assert_eq!((be.text.as_str(), be.encoding), (SAMPLE, "UTF-16BE"));
+    assert!(le.bom && be.bom);
+}
+
+#[test]
+fn test_detect_utf16_without_bom() {
+    for (big_endian, name) in [(false, "UTF-16LE"), (true, "UTF-16BE")] {
+        // ASCII を含む文章（NUL を含む）と、日本語だけの文章
+        for text in [SAMPLE, "日本語のテキスト", "plain ascii text\r\n"]
+        {
+            let detection = detect_encoding(&utf16(text, big_endian)).unwrap();
+
+            assert_eq!(detection.text, text);
+            assert_eq!(detection.encoding, name);
+            assert!(!detection.bom);
+        }
+    }
+}
+
+#[test]
+fn test_detect_euc_jp() {
+    let detection = detect_encoding(&encode(EUC_JP, SAMPLE)).unwrap();
+
+    assert_eq!(detection.text, SAMPLE);
+    assert_eq!(detection.encoding, "EUC-JP");
+    assert!(detection.confidence > 0.5);
+}
+
+#[test]
+fn test_detect_iso_2022_jp() {
+    let detection = detect_encoding(&encode(ISO_2022_JP, SAMPLE)).unwrap();
+
+    assert_eq!(detection.text, SAMPLE);
+    assert_eq!(detection.encoding, "ISO-2022-JP");
+    assert!(detection.confidence > 0.5);
+}
+
+#[test]
+fn test_detect_utf8_and_shift_jis_confidence() {
+    let utf8 = detect_encoding(SAMPLE.as_bytes()).unwrap();
+    let sjis = detect_encoding(&encode(SHIFT_JIS, SAMPLE)).unwrap();
+
+    assert_eq!(utf8.encoding, "UTF-8");
+    assert_eq!(sjis.encoding, "Shift_JIS");
+    assert_eq!(sjis.text, SAMPLE);
+    assert!(utf8.confidence > 0.5);
+    assert!(sjis.confidence > 0.5);
+}
+
+#[test]
+fn test_detect_broken_bom_is_error() {
+    // UTF-16LE の BOM の後に奇数バイト
+    let result = detect_encoding(&[0xFF, 0xFE, 0x41]);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_detect_utf8_non_japanese() {
+    // アクセント付きのラテン文字・ギリシャ文字・絵文字は採点せず UTF-8
+    for text in ["naïve café résumé\n", "Ωmega", "😀😀😀😀"] {
+        let detection = detect_encoding(text.as_bytes()).unwrap();
+
+        assert_eq!(detection.text, text);
+        assert_eq!(detection.encoding, "UTF-8");
+        assert_eq!(detection.confidence, 1.0);
+    }
+}
@@ -0,0 +1,21 @@
+use clip_frag::app::external::edit_in_editor;
+
+// 環境変数を書き換えるため、このファイルのテストは 1 つにまとめる
+#[cfg(unix)]
+#[test]
+fn test_edit_in_editor_with_scripted_editor() {
+    std::env::remove_var("VISUAL");
+    std::env::set_var("EDITOR", "sed -i s/foo/bar/");
+
+    let edited = edit_in_editor("foo\nbaz\n", Some("rs")).unwrap();
+    assert_eq!(edited, "bar\nbaz\n");
+
+    // 一時ファイルは本人だけが読み書きできる
+    std::env::set_var("EDITOR", "sh -c 'ls -l \"$0\" | cut -c1-10 > \"$0\"'");
+    let mode = edit_in_editor("secret\n", None).unwrap();
+    assert_eq!(mode, "-rw-------\n");
+
+    // エディタが失敗した場合はエラーになる
+    std::env::set_var("EDITOR", "false");
+    assert!(edit_in_editor("foo\n", None).is_err());
+}
@@ -1,5 +1,6 @@
 use clip_frag::app::fragment::{
-    build_fragment, calc_consumed_units, format_with_underscore,
+    build_fragment, build_fragment_with, calc_consumed_units,
+    format_with_underscore, GreedyLineStrategy, SplitStrategy,
 };
 use clip_frag::app::state::{AppState, Unit};
 
@@ -24,6 +25,9 @@ fn make_state(lines: Vec<&str>, max_unit: usize, unit: Unit) -> AppState {
         max_unit,
         unit,
         prev_contents: String::new(),
+        prev_is_fragment: false,
+        history: Vec::new(),
+        skipped: Vec::new(),
         curr_index: 0,
         from_file: false, // testでは常にfalse
         input_file_name: None,
@@ -74,6 +78,62 @@ fn test_build_fragment_exceeds() {
     assert_eq!(next, 1);
 }
 
+#[test]
+fn test_greedy_plan_boundaries() {
+    let state = make_state(vec!["aaa", "bbb", "ccc", "d"], 6, Unit::Chars);
+
+    let boundaries = GreedyLineStrategy.plan_boundaries(
+        &state.lines,
+        &state.line_units,
+        0,
+        state.max_unit,
+    );
+
+    assert_eq!(boundaries, vec![2, 4]);
+}
+
+#[test]
+fn test_plan_boundaries_oversized_line() {
+    // 1 行だけで max_unit を超える行は、その行単独のフラグメントになる
+    let state = make_state(vec!["aa", "bbbbbb", "c"], 3, Unit::Chars);
+
+    let boundaries = GreedyLineStrategy.plan_boundaries(
+        &state.lines,
+        &state.line_units,
+        0,
+        state.max_unit,
+    );
+
+    assert_eq!(boundaries, vec![1, 2, 3]);
+}
+
+/// 常に 1 行ずつ区切るだけのテスト用戦略
+struct OneLinePerFragment;
+
+impl SplitStrategy for OneLinePerFragment {
+    fn next_boundary(
+        &self,
+        lines: &[String],
+        _line_units: &[usize],
+        start_index: usize,
+        _max_unit: usize,
+    ) -> usize {
+        (start_index + 1).min(lines.len())
+    }
+}
+
+#[test]
+fn test_build_fragment_with_custom_strategy() {
+    let state = make_state(vec!["aa", "bb", "cc"], 100, Unit::Chars);
+
+    let (frag, used, next) =
+        build_fragment_with(&OneLinePerFragment, &state, 1);
+
+    assert_eq!(frag, "bb");
+    assert_eq!(used, 2);
+    assert_eq!(next, 2);
+}
+
 #[test]
 fn test_calc_consumed_units() {
     let state = make_state(vec!["aaa", "bb", "c"], 10, Unit::Chars);
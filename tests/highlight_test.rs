@@ -0,0 +1,67 @@
+use clip_frag::app::highlight::{escape_html, fragment_to_html, Language};
+
+#[test]
+fn test_language_from_file_name() {
+    let name = |file: &str| Language::from_file_name(file).map(|l| l.name);
+
+    assert_eq!(name("src/main.rs"), Some("rust"));
+    assert_eq!(name("APP.PY"), Some("python"));
+    assert_eq!(name("index.tsx"), Some("javascript"));
+    assert_eq!(name("notes.txt"), None);
+    assert_eq!(name("Makefile"), None);
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html("a < b && \"c\" > d"),
+        "a &lt; b &amp;&amp; &quot;c&quot; &gt; d"
+    );
+}
+
+#[test]
+fn test_fragment_to_html_without_language() {
+    assert_eq!(
+        fragment_to_html("<tag>\n", None),
+        "<pre style=\"font-family: monospace; white-space: pre;\"><code>&lt;tag&gt;\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_fragment_to_html_highlights_rust() {
+    let rust = Language::from_file_name("a.rs");
+    let html = fragment_to_html(
+        "fn f<'a>(x: &'a str) -> char { 'x' } // done\nlet n = 42;\n",
+        rust,
+    );
+
+    let span = |color: &str, text: &str| {
+        format!("<span style=\"color: {};\">{}</span>", color, text)
+    };
+
+    assert!(html.contains("class=\"language-rust\""));
+    assert!(html.contains(&span("#d73a49", "fn")));
+    assert!(html.contains(&span("#d73a49", "let")));
+    assert!(html.contains(&span("#032f62", "'x'")));
+    assert!(html.contains(&span("#6a737d", "// done")));
+    assert!(html.contains(&span("#005cc5", "42")));
+    // ライフタイムは文字列として扱わない
+    assert!(html.contains("&amp;'a str"));
+}
+
+#[test]
+fn test_fragment_to_html_highlights_python_and_sql() {
+    let python = Language::from_file_name("a.py");
+    let html =
+        fragment_to_html("def f():  # note\n    return \"s\"\n", python);
+    assert!(html.contains("<span style=\"color: #6a737d;\"># note</span>"));
+    assert!(
+        html.contains("<span style=\"color: #032f62;\">&quot;s&quot;</span>")
+    );
+
+    // SQL のキーワードは大文字・小文字を区別しない
+    let sql = Language::from_file_name("q.sql");
+    let html = fragment_to_html("SELECT 1 -- one\n", sql);
+    assert!(html.contains("<span style=\"color: #d73a49;\">SELECT</span>"));
+    assert!(html.contains("<span style=\"color: #6a737d;\">-- one</span>"));
+}
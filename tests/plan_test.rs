@@ -0,0 +1,155 @@
+use clip_frag::app::fragment::{GreedyLineStrategy, SplitStrategy};
+use clip_frag::app::plan::FragmentPlan;
+use clip_frag::app::state::{AppState, Unit};
+
+fn make_plan(text: &str, max_unit: usize) -> (AppState, FragmentPlan) {
+    let state =
+        AppState::new(text.to_string(), Unit::Chars, max_unit, false, None);
+    let plan = FragmentPlan::new(&state, &GreedyLineStrategy).unwrap();
+    (state, plan)
+}
+
+#[test]
+fn test_plan_spans() {
+    // "aaa\n" = 4, "bb\n" = 3, "c\n" = 2, "dddd\n" = 5
+    let (_, plan) = make_plan("aaa\nbb\nc\ndddd\n", 6);
+
+    assert_eq!(plan.len(), 3);
+
+    let spans: Vec<(usize, usize, usize, usize)> = plan
+        .fragments()
+        .iter()
+        .map(|f| (f.start, f.end, f.units, f.consumed_before))
+        .collect();
+    assert_eq!(spans, vec![(0, 1, 4, 0), (1, 3, 5, 4), (3, 4, 5, 9)]);
+    assert_eq!(plan.get(2).unwrap().consumed_after(), 14);
+}
+
+#[test]
+fn test_plan_fragment_at_line() {
+    let (_, plan) = make_plan("aaa\nbb\nc\ndddd\n", 6);
+
+    assert_eq!(plan.fragment_at_line(0), Some(0));
+    assert_eq!(plan.fragment_at_line(1), Some(1));
+    assert_eq!(plan.fragment_at_line(2), Some(1));
+    assert_eq!(plan.fragment_at_line(3), Some(2));
+    assert_eq!(plan.fragment_at_line(4), None);
+}
+
+#[test]
+fn test_plan_text() {
+    let (state, plan) = make_plan("aaa\nbb\nc\ndddd\n", 6);
+
+    assert_eq!(plan.text(&state, 0).as_deref(), Some("aaa\n"));
+    assert_eq!(plan.text(&state, 1).as_deref(), Some("bb\nc\n"));
+    assert_eq!(plan.text(&state, 3), None);
+}
+
+#[test]
+fn test_plan_empty_input() {
+    let (_, plan) = make_plan("", 10);
+
+    assert!(plan.is_empty());
+    assert_eq!(plan.fragment_at_line(0), None);
+}
+
+#[test]
+fn test_plan_replan_from_current_position() {
+    let (mut state, mut plan) = make_plan("aaa\nbb\nc\ndddd\n", 6);
+
+    // 1 つ目のフラグメント（L1）を取り込んだ後に、最大データ量を広げる
+    state.set_unit(Unit::Chars, 100);
+    plan.replan(&state, &GreedyLineStrategy, 1).unwrap();
+
+    let spans: Vec<(usize, usize)> =
+        plan.fragments().iter().map(|f| (f.start, f.end)).collect();
+    assert_eq!(spans, vec![(0, 1), (1, 4)]);
+    assert_eq!(plan.get(1).unwrap().consumed_before, 4);
+}
+
+#[test]
+fn test_plan_replan_with_unit_change() {
+    let (mut state, mut plan) = make_plan("あ\nい\nう\n", 4);
+
+    // chars → bytes に切り替えると、取り込み済みの単位数も計算し直される
+    state.set_unit(Unit::Bytes, 8);
+    plan.replan(&state, &GreedyLineStrategy, 2).unwrap();
+
+    let spans: Vec<(usize, usize, usize)> =
+        plan.fragments().iter().map(|f| (f.start, f.end, f.units)).collect();
+    assert_eq!(spans, vec![(0, 2, 8), (2, 3, 4)]);
+    assert_eq!(state.total_units, 12);
+}
+
+#[test]
+fn test_plan_set_end() {
+    // "a\n" = 2 chars ずつ、max 4 → [0,2) [2,4) [4,5)
+    let (state, mut plan) = make_plan("a\nb\nc\nd\ne\n", 4);
+    assert_eq!(plan.len(), 3);
+
+    // 1 つ目を 1 行広げると、後ろは分割し直される
+    plan.set_end(&state, &GreedyLineStrategy, 0, 3).unwrap();
+
+    let spans: Vec<(usize, usize)> =
+        plan.fragments().iter().map(|f| (f.start, f.end)).collect();
+    assert_eq!(spans, vec![(0, 3), (3, 5)]);
+    assert_eq!(plan.get(0).unwrap().units, 6);
+
+    // 開始行以下には縮められない
+    plan.set_end(&state, &GreedyLineStrategy, 1, 0).unwrap();
+    assert_eq!(plan.get(1).map(|f| (f.start, f.end)), Some((3, 4)));
+}
+
+/// 決まった境界を返すだけのテスト用戦略（plan_boundaries の約束を破れる）
+struct FixedBoundaries(Vec<usize>);
+
+impl SplitStrategy for FixedBoundaries {
+    fn next_boundary(
+        &self,
+        lines: &[String],
+        _line_units: &[usize],
+        _start_index: usize,
+        _max_unit: usize,
+    ) -> usize {
+        lines.len()
+    }
+
+    fn plan_boundaries(
+        &self,
+        _lines: &[String],
+        _line_units: &[usize],
+        _start_index: usize,
+        _max_unit: usize,
+    ) -> Vec<usize> {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn test_plan_rejects_invalid_boundaries() {
+    let (state, mut plan) = make_plan("a\nb\nc\nd\n", 4);
+    let before = plan.fragments().to_vec();
+
+    for boundaries in [
+        vec![2, 2, 4], // 増えていない
+        vec![3, 1, 4], // 戻っている
+        vec![2, 5],    // 行数を超えている
+        vec![1, 3],    // 末尾まで届いていない
+        vec![],        // 空
+    ] {
+        let strategy = FixedBoundaries(boundaries.clone());
+        assert!(
+            FragmentPlan::new(&state, &strategy).is_err(),
+            "{:?}",
+            boundaries
+        );
+    }
+
+    // from_line 以下の境界もエラーにし、分割計画は変更しない
+    assert!(plan.replan(&state, &FixedBoundaries(vec![1, 4]), 2).is_err());
+    assert!(plan.set_end(&state, &FixedBoundaries(vec![]), 0, 1).is_err());
+    assert_eq!(plan.fragments(), &before[..]);
+
+    let strategy = FixedBoundaries(vec![1, 4]);
+    assert_eq!(FragmentPlan::new(&state, &strategy).unwrap().len(), 2);
+}
@@ -0,0 +1,26 @@
+use clip_frag::app::preview::format_preview;
+
+fn lines(n: usize) -> Vec<String> {
+    (1..=n).map(|i| format!("line {}\n", i)).collect()
+}
+
+#[test]
+fn test_preview_short_fragment_shows_all() {
+    let lines = lines(3);
+
+    let preview = format_preview(&lines, &(0..3), 2, 2);
+
+    assert_eq!(preview, "  1 | line 1\n  2 | line 2\n  3 | line 3\n");
+}
+
+#[test]
+fn test_preview_long_fragment_is_omitted() {
+    let lines = lines(20);
+
+    let preview = format_preview(&lines, &(7..12), 2, 1);
+
+    assert_eq!(
+        preview,
+        "   8 | line 8\n   9 | line 9\n     | ... (2 行省略)\n  12 | line 12\n"
+    );
+}
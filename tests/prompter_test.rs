@@ -0,0 +1,38 @@
+use clip_frag::app::{
+    AutoAdvancePrompter, Prompter, ScriptExhausted, ScriptPrompter,
+};
+
+#[test]
+fn test_script_prompter_from_commands() {
+    let mut p = ScriptPrompter::from_commands("y, y,p,,q");
+    assert_eq!(p.remaining(), 5);
+
+    let read: Vec<String> =
+        (0..5).map(|_| p.read_choice(&[]).unwrap()).collect();
+    assert_eq!(read, vec!["y", "y", "p", "", "q"]);
+
+    // 使い切ったら ScriptExhausted（App は Quit として扱う）。
+    // 自由入力は空行（中止）
+    assert!(p.read_choice(&[]).unwrap_err().is::<ScriptExhausted>());
+    assert_eq!(p.read_line().unwrap(), "");
+}
+
+#[test]
+fn test_script_prompter_from_lines() {
+    let mut p = ScriptPrompter::from_lines("# comment\ny\n\n/foo,bar\ng 3\n");
+
+    assert_eq!(p.remaining(), 4);
+    assert_eq!(p.read_choice(&[]).unwrap(), "y");
+    assert_eq!(p.read_choice(&[]).unwrap(), "");
+    // 1 行 1 コマンドなので、カンマで区切らない
+    assert_eq!(p.read_choice(&[]).unwrap(), "/foo,bar");
+    assert_eq!(p.read_line().unwrap(), "g 3");
+}
+
+#[test]
+fn test_auto_advance_prompter() {
+    let mut p = AutoAdvancePrompter::new(std::time::Duration::ZERO);
+
+    assert_eq!(p.read_choice(&[]).unwrap(), "");
+    assert_eq!(p.read_line().unwrap(), "");
+}
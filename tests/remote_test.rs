@@ -0,0 +1,199 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use clip_frag::app::clipboard::{
+    ClipboardBackend, ClipboardSink, Endpoint, RemoteClipboard,
+};
+use clip_frag::app::serve::{
+    generate_token, serve, serve_connection, Listener,
+};
+
+/// 受け取った内容をチャネルに流す ClipboardSink（クリアは ""）
+struct ChannelClipboard(Sender<String>);
+
+impl ClipboardSink for ChannelClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.0.send(text.to_string())?;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.0.send(String::new())?;
+        Ok(())
+    }
+}
+
+/// エフェメラルポートで待ち受け、接続を 1 つだけ処理するサーバを起動する
+fn spawn_server(
+    token: &'static str,
+) -> (
+    Endpoint,
+    thread::JoinHandle<Result<()>>,
+    std::sync::mpsc::Receiver<String>,
+) {
+    let listener = Listener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let endpoint = listener.local_endpoint().unwrap();
+    let (tx, rx) = channel();
+
+    let handle = thread::spawn(move || {
+        let stream = listener.accept()?;
+        serve_connection(stream, token, &mut ChannelClipboard(tx))
+    });
+
+    (endpoint, handle, rx)
+}
+
+#[test]
+fn test_remote_set_and_clear() {
+    let (endpoint, handle, rx) = spawn_server("secret");
+
+    let mut client = RemoteClipboard::new(endpoint, Some("secret".into()));
+    client.set_text("あいう\nえお\n").unwrap();
+    client.set_text("").unwrap();
+    client.clear().unwrap();
+    drop(client);
+
+    handle.join().unwrap().unwrap();
+    let received: Vec<String> = rx.iter().collect();
+    assert_eq!(received, vec!["あいう\nえお\n", "", ""]);
+}
+
+#[test]
+fn test_remote_rejects_wrong_token() {
+    let (endpoint, handle, rx) = spawn_server("secret");
+
+    let mut client = RemoteClipboard::new(endpoint, Some("guess".into()));
+    let err = client.set_text("injected").unwrap_err();
+    assert!(format!("{:#}", err).contains("トークン"));
+
+    assert!(handle.join().unwrap().is_err());
+    assert!(rx.iter().next().is_none());
+}
+
+#[test]
+fn test_serve_replies_err_to_invalid_commands() {
+    for command in ["SET abc\n", "SET 999999999999\n", "PASTE\n"] {
+        let (endpoint, handle, rx) = spawn_server("secret");
+        let Endpoint::Tcp(addr) = endpoint else { unreachable!() };
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"CLIP_FRAG/1 secret\n").unwrap();
+        stream.write_all(command.as_bytes()).unwrap();
+        let lines: Vec<String> =
+            BufReader::new(stream).lines().map(|l| l.unwrap()).collect();
+
+        // 切断される前に ERR が返り、その接続だけがエラーで終わる
+        assert_eq!(lines.len(), 2, "{:?}", lines);
+        assert_eq!(lines[0], "OK");
+        assert!(lines[1].starts_with("ERR "), "{:?}", lines);
+        assert!(handle.join().unwrap().is_err());
+        assert!(rx.iter().next().is_none());
+    }
+}
+
+#[test]
+fn test_serve_drops_silent_connection() {
+    let listener = Listener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let endpoint = listener.local_endpoint().unwrap();
+    let (tx, rx) = channel();
+
+    thread::spawn(move || {
+        serve(&listener, "secret", &mut ChannelClipboard(tx))
+    });
+
+    // 何も送らない接続があっても、HELLO_TIMEOUT の後に次の接続を受け付ける
+    let Endpoint::Tcp(ref addr) = endpoint else { unreachable!() };
+    let _silent = TcpStream::connect(addr).unwrap();
+
+    let mut client = RemoteClipboard::new(endpoint, Some("secret".into()));
+    client.set_text("after silence").unwrap();
+
+    assert_eq!(rx.recv().unwrap(), "after silence");
+}
+
+#[test]
+fn test_serve_drops_slow_hello() {
+    let listener = Listener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let endpoint = listener.local_endpoint().unwrap();
+    let (tx, rx) = channel();
+
+    thread::spawn(move || {
+        serve(&listener, "secret", &mut ChannelClipboard(tx))
+    });
+
+    // 1 バイトずつ送り続ける接続も、HELLO_TIMEOUT の後には切断される
+    let Endpoint::Tcp(ref addr) = endpoint else { unreachable!() };
+    let mut slow = TcpStream::connect(addr).unwrap();
+    thread::spawn(move || {
+        for _ in 0..60 {
+            if slow.write_all(b"C").is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let mut client = RemoteClipboard::new(endpoint, Some("secret".into()));
+    client.set_text("after slow hello").unwrap();
+
+    assert_eq!(rx.recv().unwrap(), "after slow hello");
+}
+
+#[test]
+fn test_generate_token() {
+    let (a, b) = (generate_token().unwrap(), generate_token().unwrap());
+
+    assert_eq!(a.len(), 32);
+    assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_remote_requires_token() {
+    let mut client =
+        RemoteClipboard::new("127.0.0.1:1".parse().unwrap(), None);
+
+    assert!(client.set_text("x").is_err());
+}
+
+#[test]
+fn test_parse_remote_backend() {
+    assert_eq!(
+        "remote:127.0.0.1:7878".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::Remote(Endpoint::Tcp("127.0.0.1:7878".into()))
+    );
+    assert_eq!(
+        "remote:unix:/tmp/clip.sock".parse::<ClipboardBackend>().unwrap(),
+        ClipboardBackend::Remote(Endpoint::Unix("/tmp/clip.sock".into()))
+    );
+    assert!("remote:".parse::<ClipboardBackend>().is_err());
+    assert!("remote:unix:".parse::<ClipboardBackend>().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remote_over_unix_socket() {
+    let path = std::env::temp_dir()
+        .join(format!("clip_frag_remote_{}.sock", std::process::id()));
+    let endpoint = Endpoint::Unix(path.clone());
+    let listener = Listener::bind(&endpoint).unwrap();
+    let (tx, rx) = channel();
+
+    let handle = thread::spawn(move || {
+        let stream = listener.accept()?;
+        serve_connection(stream, "t", &mut ChannelClipboard(tx))
+    });
+
+    let mut client = RemoteClipboard::new(endpoint, Some("t".into()));
+    client.set_text("fragment").unwrap();
+    drop(client);
+
+    handle.join().unwrap().unwrap();
+    assert_eq!(rx.iter().collect::<Vec<_>>(), vec!["fragment"]);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,37 @@
+use clip_frag::app::fragment::GreedyLineStrategy;
+use clip_frag::app::plan::FragmentPlan;
+use clip_frag::app::search::{search_lines, SearchHit};
+use clip_frag::app::state::{AppState, Unit};
+
+fn make_state() -> (AppState, FragmentPlan) {
+    let text = "fn main() {\n}\nfn finalize_loop() {\n}\n";
+    let state = AppState::new(text.to_string(), Unit::Chars, 14, false, None);
+    let plan = FragmentPlan::new(&state, &GreedyLineStrategy).unwrap();
+    (state, plan)
+}
+
+#[test]
+fn test_search_lines_regex() {
+    let (state, plan) = make_state();
+
+    let hits = search_lines(&state.lines, &plan, r"fn \w+_loop").unwrap();
+
+    assert_eq!(hits, vec![SearchHit { line: 2, fragment: 1 }]);
+}
+
+#[test]
+fn test_search_lines_multiple_hits() {
+    let (state, plan) = make_state();
+
+    let hits = search_lines(&state.lines, &plan, "^}").unwrap();
+
+    let lines: Vec<usize> = hits.iter().map(|h| h.line).collect();
+    assert_eq!(lines, vec![1, 3]);
+}
+
+#[test]
+fn test_search_lines_invalid_pattern() {
+    let (state, plan) = make_state();
+
+    assert!(search_lines(&state.lines, &plan, "(").is_err());
+}
@@ -46,4 +46,6 @@ fn test_state_metadata() {
     assert_eq!(st.input_file_name.as_deref(), Some("file.txt"));
     assert_eq!(st.curr_index, 0);
     assert_eq!(st.prev_contents, "");
+    assert!(st.history.is_empty());
+    assert!(st.skipped.is_empty());
 }
@@ -0,0 +1,84 @@
+use clip_frag::app::summary::{
+    format_line_range, fragment_status, never_sent_ranges, FragmentStatus,
+};
+
+#[test]
+fn test_never_sent_ranges() {
+    // 0..2 と 4..6、8..10 を送信（2..4 と 6..8 はスキップ）
+    let history = [0..1, 1..2, 4..6, 8..10];
+
+    assert_eq!(never_sent_ranges(10, &history), vec![2..4, 6..8]);
+}
+
+#[test]
+fn test_never_sent_ranges_resent_later() {
+    // 飛ばした後に一部を取り込み直した場合は、その行を除く
+    let history = vec![0..2, 3..4];
+
+    assert_eq!(never_sent_ranges(5, &history), vec![2..3, 4..5]);
+}
+
+#[test]
+fn test_never_sent_ranges_merges_adjacent() {
+    let history = vec![0..2, 6..8];
+
+    assert_eq!(never_sent_ranges(8, &history), vec![2..6]);
+}
+
+#[test]
+fn test_never_sent_ranges_includes_jumped_and_remaining_lines() {
+    // 0..2 を送信し、G で 6..8 へ移動して送信、そこで終了した
+    let history = vec![0..2, 6..8];
+
+    assert_eq!(never_sent_ranges(10, &history), vec![2..6, 8..10]);
+    // 何も送らずに終了した場合は全体
+    assert_eq!(never_sent_ranges(3, &[]), vec![0..3]);
+    // すべて送信した場合は空
+    assert!(never_sent_ranges(4, &[0..2, 2..4]).is_empty());
+}
+
+#[test]
+fn test_format_line_range() {
+    assert_eq!(format_line_range(&(119..348)), "L120–L348");
+    assert_eq!(format_line_range(&(4..5)), "L5");
+}
+
+#[test]
+fn test_fragment_status() {
+    let history = [0..2, 5..9];
+    let skipped = [2..4, 4..5];
+    let never_sent = never_sent_ranges(12, &history);
+
+    assert_eq!(
+        fragment_status(&(0..2), &never_sent, &skipped),
+        FragmentStatus::Sent
+    );
+    assert_eq!(
+        fragment_status(&(6..8), &never_sent, &skipped),
+        FragmentStatus::Sent
+    );
+    assert_eq!(
+        fragment_status(&(2..4), &never_sent, &skipped),
+        FragmentStatus::Skipped
+    );
+    assert_eq!(
+        fragment_status(&(9..12), &never_sent, &skipped),
+        FragmentStatus::Pending
+    );
+}
+
+#[test]
+fn test_fragment_status_sent_across_several_copies() {
+    // 分割し直した後のフラグメント 1..5 は、2 回に分けて取り込み済み
+    let history = [0..3, 3..6];
+    let never_sent = never_sent_ranges(8, &history);
+
+    assert_eq!(
+        fragment_status(&(1..5), &never_sent, &[]),
+        FragmentStatus::Sent
+    );
+    assert_eq!(
+        fragment_status(&(5..8), &never_sent, &[]),
+        FragmentStatus::Pending
+    );
+}
@@ -0,0 +1,70 @@
+use clip_frag::app::tty::{
+    escape_sequence_complete, key_to_input, parse_key, Key, KeyInput,
+};
+
+#[test]
+fn test_parse_key() {
+    assert_eq!(parse_key(b"y"), Key::Char('y'));
+    assert_eq!(parse_key(b"\r"), Key::Enter);
+    assert_eq!(parse_key(b"\n"), Key::Enter);
+    assert_eq!(parse_key(&[0x7f]), Key::Backspace);
+    assert_eq!(parse_key(&[0x1b]), Key::Esc);
+    assert_eq!(parse_key(b"\x1b[A"), Key::Up);
+    assert_eq!(parse_key(b"\x1bOD"), Key::Left);
+    // PgUp・Del・F5・Ctrl+↑ などは 1 つの Other
+    assert_eq!(parse_key(b"\x1b[5~"), Key::Other);
+    assert_eq!(parse_key(b"\x1b[3~"), Key::Other);
+    assert_eq!(parse_key(b"\x1b[15~"), Key::Other);
+    assert_eq!(parse_key(b"\x1b[1;5A"), Key::Other);
+    assert_eq!(parse_key("あ".as_bytes()), Key::Char('あ'));
+    assert_eq!(parse_key(&[0x01]), Key::Other);
+}
+
+#[test]
+fn test_key_to_input() {
+    let starters = ['/', 'g', 'm'];
+    let input = |key| key_to_input(key, &starters);
+
+    assert_eq!(input(Key::Enter), KeyInput::Immediate(String::new()));
+    assert_eq!(input(Key::Char(' ')), KeyInput::Immediate("space".into()));
+    assert_eq!(input(Key::Char('q')), KeyInput::Immediate("q".into()));
+    assert_eq!(input(Key::Left), KeyInput::Immediate("left".into()));
+    assert_eq!(input(Key::Right), KeyInput::Immediate("right".into()));
+    assert_eq!(input(Key::Char('/')), KeyInput::NeedsLine('/'));
+    assert_eq!(input(Key::Char('G')), KeyInput::NeedsLine('G'));
+    assert_eq!(input(Key::Char('3')), KeyInput::NeedsLine('3'));
+    assert_eq!(input(Key::Esc), KeyInput::Ignore);
+}
+
+#[test]
+fn test_key_to_input_custom_starters() {
+    // goto を "j" に割り当て直した場合、g はその場で確定する
+    let starters = ['/', 'j'];
+
+    assert_eq!(
+        key_to_input(Key::Char('g'), &starters),
+        KeyInput::Immediate("g".into())
+    );
+    assert_eq!(
+        key_to_input(Key::Char('j'), &starters),
+        KeyInput::NeedsLine('j')
+    );
+}
+
+#[test]
+fn test_escape_sequence_complete() {
+    // 続きを待つ
+    assert!(!escape_sequence_complete(b"\x1b"));
+    assert!(!escape_sequence_complete(b"\x1b["));
+    assert!(!escape_sequence_complete(b"\x1b[5"));
+    assert!(!escape_sequence_complete(b"\x1b[1;5"));
+    assert!(!escape_sequence_complete(b"\x1bO"));
+
+    // そろった
+    assert!(escape_sequence_complete(b"\x1b[A"));
+    assert!(escape_sequence_complete(b"\x1b[5~"));
+    assert!(escape_sequence_complete(b"\x1b[15~"));
+    assert!(escape_sequence_complete(b"\x1b[1;5A"));
+    assert!(escape_sequence_complete(b"\x1bOD"));
+    assert!(escape_sequence_complete(b"\x1bx"));
+}